@@ -0,0 +1,23 @@
+//! Host-side helpers for indexers and clients of the flash loan program, kept out of the program so
+//! that it carries no decoding code.
+
+use pinocchio_flash_loan::Event;
+
+/// Decoding of the events logged by the program with `Event::emit`
+pub trait DecodeEvent: Event {
+    /// decodes the event from the base64-decoded fields of a `Program data:` log line,
+    /// `None` when the log holds another event
+    fn decode(fields: &[&[u8]]) -> Option<Self> {
+        let [discriminator, event_bytes] = fields else {
+            return None;
+        };
+        if *discriminator != [Self::DISCRIMINATOR] || event_bytes.len() != core::mem::size_of::<Self>() {
+            return None;
+        }
+
+        // Events are packed and only hold integers and pubkeys, so any bytes of the right length are a valid event
+        Some(unsafe { core::ptr::read_unaligned(event_bytes.as_ptr() as *const Self) })
+    }
+}
+
+impl<E: Event> DecodeEvent for E {}
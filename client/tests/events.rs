@@ -0,0 +1,47 @@
+use pinocchio_flash_loan::{DeadManSwitchUpdated, EmergencyMode, Event, LoanRepaid, ModeChanged, Paused, PAUSE_LOAN};
+use pinocchio_flash_loan_client::DecodeEvent;
+
+/// encode an event the way `Event::emit` logs it, as the discriminator and the packed event bytes
+fn encode<E: Event>(event: &E) -> (Vec<u8>, Vec<u8>) {
+    let event_bytes = unsafe {
+        core::slice::from_raw_parts(event as *const E as *const u8, core::mem::size_of::<E>())
+    };
+    (vec![E::DISCRIMINATOR], event_bytes.to_vec())
+}
+
+#[test]
+fn test_event_layout() {
+    // packed, little-endian, no padding
+    assert_eq!(core::mem::size_of::<LoanRepaid>(), 32 + 32 + 8 + 8);
+    assert_eq!(core::mem::size_of::<Paused>(), 32 + 32 + 2 + 8);
+    assert_eq!(core::mem::size_of::<ModeChanged>(), 32 + 1 + 1);
+    assert_eq!(core::mem::size_of::<DeadManSwitchUpdated>(), 32 + 8 + 1);
+
+    let event = LoanRepaid { borrower: [1u8; 32], mint: [2u8; 32], amount: 1_000_000, fee: 5_000 };
+    let (_, event_bytes) = encode(&event);
+    assert_eq!(&event_bytes[0..32], &[1u8; 32]);
+    assert_eq!(&event_bytes[32..64], &[2u8; 32]);
+    assert_eq!(u64::from_le_bytes(event_bytes[64..72].try_into().unwrap()), 1_000_000);
+    assert_eq!(u64::from_le_bytes(event_bytes[72..80].try_into().unwrap()), 5_000);
+}
+
+#[test]
+fn test_event_decoding() {
+    let event = Paused { authority: [3u8; 32], mint: [0u8; 32], instructions: PAUSE_LOAN, expiry: 86_400 };
+    let (discriminator, event_bytes) = encode(&event);
+
+    let decoded = Paused::decode(&[&discriminator, &event_bytes]).unwrap();
+    assert_eq!({ decoded.authority }, [3u8; 32]);
+    assert_eq!({ decoded.instructions }, PAUSE_LOAN);
+    assert_eq!({ decoded.expiry }, 86_400);
+
+    // other events, truncated data and missing fields are rejected
+    assert!(ModeChanged::decode(&[&discriminator, &event_bytes]).is_none());
+    assert!(Paused::decode(&[&discriminator, &event_bytes[1..]]).is_none());
+    assert!(Paused::decode(&[&event_bytes]).is_none());
+
+    let event = ModeChanged { authority: [0u8; 32], old_mode: EmergencyMode::Normal as u8, new_mode: EmergencyMode::Limited as u8 };
+    let (discriminator, event_bytes) = encode(&event);
+    let decoded = ModeChanged::decode(&[&discriminator, &event_bytes]).unwrap();
+    assert_eq!(EmergencyMode::try_from(decoded.new_mode).unwrap(), EmergencyMode::Limited);
+}
@@ -0,0 +1,19 @@
+use pinocchio::program_error::ProgramError;
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FlashLoanError {
+    ModeUnchanged = 0,              // Emergency mode transition to the current mode
+    ModeSkipsLevel = 1,             // De-escalation by more than one level at once
+    ModeCooldownActive = 2,         // De-escalation before the current mode's dwell time passed
+    HeartbeatNotExpired = 3,        // Dead-man switch triggered while the admin is still active
+    BorrowerNotAllowed = 4,         // Loan from a borrower missing from the allowlist of a permissioned pool
+    NotFrozen = 5,                  // Vault evacuation outside of EmergencyMode::Frozen
+    MultisigActive = 6,             // Admin key transfer while the admin multisig, not the admin key, authorizes admin operations
+}
+
+impl From<FlashLoanError> for ProgramError {
+    fn from(error: FlashLoanError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
@@ -0,0 +1,444 @@
+use pinocchio::{log::sol_log_data, pubkey::Pubkey};
+
+use crate::{Reserve, MAX_FEE_CURVE_POINTS, MAX_FEE_TIERS};
+
+/// Structured event logged with `sol_log_data` as two fields, the event discriminator and the
+/// event's packed little-endian bytes. Discriminators follow the instruction emitting the event,
+/// events only hold integers and pubkeys so that any bytes of the right length decode, which the
+/// client crate relies on.
+pub trait Event: Copy {
+    const DISCRIMINATOR: u8;
+
+    fn emit(&self) {
+        let event_bytes = unsafe {
+            core::slice::from_raw_parts(self as *const Self as *const u8, core::mem::size_of::<Self>())
+        };
+        sol_log_data(&[&[Self::DISCRIMINATOR], event_bytes]);
+    }
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct LoanTaken {
+    pub borrower: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+impl Event for LoanTaken {
+    const DISCRIMINATOR: u8 = 0;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct LoanRepaid {
+    pub borrower: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+impl Event for LoanRepaid {
+    const DISCRIMINATOR: u8 = 1;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct EmergencyInitialized {
+    pub admin: Pubkey,
+    pub pool_id: u64,
+}
+
+impl Event for EmergencyInitialized {
+    const DISCRIMINATOR: u8 = 10;
+}
+
+/// Emitted by Pause and PauseWithSignature, with the default pubkey as mint for a protocol-wide pause
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct Paused {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub instructions: u16,              // Paused instruction flags, 0 for every instruction
+    pub expiry: i64,                    // Timestamp the pause lapses at, 0 for no expiry
+}
+
+impl Event for Paused {
+    const DISCRIMINATOR: u8 = 11;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct Unpaused {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub instructions: u16,              // Unpaused instruction flags, 0 for every instruction
+}
+
+impl Event for Unpaused {
+    const DISCRIMINATOR: u8 = 12;
+}
+
+/// Emitted on any emergency mode change, with the default pubkey as authority when the circuit
+/// breaker or the dead-man switch changed it, or the operation account for a queued change
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct ModeChanged {
+    pub authority: Pubkey,
+    pub old_mode: u8,
+    pub new_mode: u8,
+}
+
+impl Event for ModeChanged {
+    const DISCRIMINATOR: u8 = 13;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct LimitsUpdated {
+    pub authority: Pubkey,
+    pub mode_limit_bps: [u16; 4],
+    pub mode_fee_surcharge_bps: [u16; 4],
+}
+
+impl Event for LimitsUpdated {
+    const DISCRIMINATOR: u8 = 14;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct AdminTransferStarted {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+impl Event for AdminTransferStarted {
+    const DISCRIMINATOR: u8 = 15;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct AdminTransferred {
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+impl Event for AdminTransferred {
+    const DISCRIMINATOR: u8 = 16;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct GuardiansUpdated {
+    pub authority: Pubkey,
+    pub guardian_count: u8,
+}
+
+impl Event for GuardiansUpdated {
+    const DISCRIMINATOR: u8 = 17;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct AdminMultisigUpdated {
+    pub authority: Pubkey,
+    pub threshold: u8,
+    pub signer_count: u8,
+}
+
+impl Event for AdminMultisigUpdated {
+    const DISCRIMINATOR: u8 = 18;
+}
+
+/// Emitted by RegisterReserve and UpdateReserve, as well as queued reserve updates
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct ReserveUpdated {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub is_enabled: u8,
+    pub max_loan_amount: u64,
+    pub max_total_outstanding: u64,
+    pub volume_threshold: u64,
+}
+
+impl Event for ReserveUpdated {
+    const DISCRIMINATOR: u8 = 21;
+}
+
+impl ReserveUpdated {
+    pub fn from_reserve(authority: Pubkey, reserve: &Reserve) -> Self {
+        Self {
+            authority,
+            mint: reserve.mint,
+            is_enabled: reserve.is_enabled as u8,
+            max_loan_amount: reserve.max_loan_amount,
+            max_total_outstanding: reserve.max_total_outstanding,
+            volume_threshold: reserve.volume_threshold,
+        }
+    }
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct FeeTiersUpdated {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub fee_bps: u16,
+    pub fee_tier_volumes: [u64; MAX_FEE_TIERS],
+    pub fee_tier_bps: [u16; MAX_FEE_TIERS],
+}
+
+impl Event for FeeTiersUpdated {
+    const DISCRIMINATOR: u8 = 22;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct FeeCurveUpdated {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub fee_curve_utilization_bps: [u16; MAX_FEE_CURVE_POINTS],
+    pub fee_curve_bps: [u16; MAX_FEE_CURVE_POINTS],
+}
+
+impl Event for FeeCurveUpdated {
+    const DISCRIMINATOR: u8 = 23;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct MinFeeUpdated {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub min_fee: u64,
+}
+
+impl Event for MinFeeUpdated {
+    const DISCRIMINATOR: u8 = 24;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct VaultInitialized {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub protocol: Pubkey,
+}
+
+impl Event for VaultInitialized {
+    const DISCRIMINATOR: u8 = 25;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct KeeperUpdated {
+    pub authority: Pubkey,
+    pub keeper: Pubkey,
+    pub is_keeper: u8,
+    pub fee_bps: u16,
+    pub limit_bps: u16,
+}
+
+impl Event for KeeperUpdated {
+    const DISCRIMINATOR: u8 = 50;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct AllowlistUpdated {
+    pub authority: Pubkey,
+    pub borrower: Pubkey,
+    pub is_allowed: u8,
+}
+
+impl Event for AllowlistUpdated {
+    const DISCRIMINATOR: u8 = 51;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct PermissionedUpdated {
+    pub authority: Pubkey,
+    pub is_permissioned: u8,
+}
+
+impl Event for PermissionedUpdated {
+    const DISCRIMINATOR: u8 = 52;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct GateProgramUpdated {
+    pub authority: Pubkey,
+    pub gate_program: Pubkey,
+    pub has_gate_program: u8,
+}
+
+impl Event for GateProgramUpdated {
+    const DISCRIMINATOR: u8 = 53;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct ReferralShareUpdated {
+    pub authority: Pubkey,
+    pub referral_share_bps: u16,
+}
+
+impl Event for ReferralShareUpdated {
+    const DISCRIMINATOR: u8 = 60;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct ReferralClaimed {
+    pub referrer: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+impl Event for ReferralClaimed {
+    const DISCRIMINATOR: u8 = 61;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct ReferralCredited {
+    pub referrer: Pubkey,
+    pub borrower: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+impl Event for ReferralCredited {
+    const DISCRIMINATOR: u8 = 62;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct ReferrerRegistered {
+    pub authority: Pubkey,
+    pub referrer: Pubkey,
+    pub mint: Pubkey,
+}
+
+impl Event for ReferrerRegistered {
+    const DISCRIMINATOR: u8 = 63;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct TreasuryUpdated {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub sweep_when_frozen: u8,
+}
+
+impl Event for TreasuryUpdated {
+    const DISCRIMINATOR: u8 = 70;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct FeesSwept {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub treasury_token_account: Pubkey,
+    pub amount: u64,
+}
+
+impl Event for FeesSwept {
+    const DISCRIMINATOR: u8 = 71;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct RecoveryUpdated {
+    pub authority: Pubkey,
+    pub recovery: Pubkey,
+    pub has_recovery: u8,
+}
+
+impl Event for RecoveryUpdated {
+    const DISCRIMINATOR: u8 = 80;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct VaultEvacuated {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub recovery_token_account: Pubkey,
+    pub amount: u64,
+}
+
+impl Event for VaultEvacuated {
+    const DISCRIMINATOR: u8 = 81;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct OperationQueued {
+    pub authority: Pubkey,
+    pub operation: Pubkey,
+    pub id: u64,
+    pub action: u8,
+    pub eta: i64,
+}
+
+impl Event for OperationQueued {
+    const DISCRIMINATOR: u8 = 30;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct OperationExecuted {
+    pub operation: Pubkey,
+    pub id: u64,
+    pub action: u8,
+}
+
+impl Event for OperationExecuted {
+    const DISCRIMINATOR: u8 = 31;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct OperationCancelled {
+    pub authority: Pubkey,
+    pub operation: Pubkey,
+    pub id: u64,
+    pub action: u8,
+}
+
+impl Event for OperationCancelled {
+    const DISCRIMINATOR: u8 = 32;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct HeartbeatRecorded {
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+impl Event for HeartbeatRecorded {
+    const DISCRIMINATOR: u8 = 40;
+}
+
+/// Emitted when Heartbeat tightens the dead-man switch settings or a queued change to them is executed
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct DeadManSwitchUpdated {
+    pub authority: Pubkey,
+    pub heartbeat_interval: i64,
+    pub dead_man_mode: u8,
+}
+
+impl Event for DeadManSwitchUpdated {
+    const DISCRIMINATOR: u8 = 42;
+}
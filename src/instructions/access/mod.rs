@@ -0,0 +1,9 @@
+pub mod set_keeper;
+pub mod update_allowlist;
+pub mod set_permissioned;
+pub mod set_gate_program;
+
+pub use set_keeper::*;
+pub use update_allowlist::*;
+pub use set_permissioned::*;
+pub use set_gate_program::*;
@@ -0,0 +1,158 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, load_emergency_state, save_emergency_state, verify_admin, AuditAction, AuditEntry, EmergencyState, Event,
+    GateProgramUpdated,
+};
+
+pub struct SetGateProgramAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    /// Gate program to set, ignored when removing the gate
+    pub gate_program: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetGateProgramAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, gate_program, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            gate_program,
+            signers,
+        })
+    }
+}
+
+/// `[has_gate_program (u8)][reason (u8)?]`
+pub struct SetGateProgramInstructionData {
+    /// Whether loans go through the gate program, `false` removing the gate
+    pub has_gate_program: bool,
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for SetGateProgramInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let has_gate_program = match data.first() {
+            Some(0) => false,
+            Some(1) => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self {
+            has_gate_program,
+            reason: data.get(1).copied().unwrap_or(0),
+        })
+    }
+}
+
+impl SetGateProgramInstructionData {
+    /// Whether the change removes or replaces the current gate, in which case it has to go through the timelock
+    pub fn loosens(&self, state: &EmergencyState, gate_program: &Pubkey) -> bool {
+        state.has_gate_program && (!self.has_gate_program || state.gate_program != *gate_program)
+    }
+
+    /// checks that a gate being set is a deployed program, as Loan invokes it
+    pub fn validate(&self, gate_program: &AccountInfo) -> ProgramResult {
+        if self.has_gate_program && !gate_program.executable() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        Ok(())
+    }
+
+    pub fn apply(&self, state: &mut EmergencyState, gate_program: &Pubkey) {
+        state.has_gate_program = self.has_gate_program;
+        state.gate_program = if self.has_gate_program { *gate_program } else { Pubkey::default() };
+    }
+}
+
+/// records a gate program change in the audit log, emitting a GateProgramUpdated event
+pub fn log_gate_program_update(audit_log: &AccountInfo, pool: &Pubkey, actor: &Pubkey, old: &EmergencyState, new: &EmergencyState, reason: u8, now: i64) -> ProgramResult {
+    let target = if new.has_gate_program { new.gate_program } else { old.gate_program };
+    append_audit_entry(audit_log, pool, &AuditEntry::new(
+        *actor, AuditAction::SetGateProgram, target, old.has_gate_program as u64, new.has_gate_program as u64, reason, now,
+    ))?;
+
+    GateProgramUpdated {
+        authority: *actor,
+        gate_program: new.gate_program,
+        has_gate_program: new.has_gate_program as u8,
+    }.emit();
+
+    Ok(())
+}
+
+pub struct SetGateProgram<'a> {
+    pub accounts: SetGateProgramAccounts<'a>,
+    pub instruction_data: SetGateProgramInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetGateProgram<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SetGateProgramAccounts::try_from(accounts)?;
+        let instruction_data = SetGateProgramInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetGateProgram<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &53;
+
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        self.instruction_data.validate(self.accounts.gate_program)?;
+
+        // Adding a gate to an ungated pool applies immediately, removing or replacing it is queued with QueueOperation
+        let gate_program = self.accounts.gate_program.key();
+        if self.instruction_data.loosens(&emergency_state, gate_program) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let mut updated_state = emergency_state;
+        self.instruction_data.apply(&mut updated_state, gate_program);
+        updated_state.last_updated = Clock::get()?.unix_timestamp;
+        save_emergency_state(self.accounts.emergency_account, &updated_state)?;
+
+        log_gate_program_update(
+            self.accounts.audit_log,
+            self.accounts.emergency_account.key(),
+            self.accounts.admin.key(),
+            &emergency_state,
+            &updated_state,
+            self.instruction_data.reason,
+            updated_state.last_updated,
+        )
+    }
+}
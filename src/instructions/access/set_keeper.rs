@@ -0,0 +1,176 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, load_emergency_state, load_keeper_whitelist, save_keeper_whitelist, verify_admin, AuditAction, AuditEntry,
+    Event, Keeper, KeeperUpdated, KeeperWhitelist,
+};
+
+pub struct SetKeeperAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    pub keepers: &'a AccountInfo,
+    /// Borrower or program to whitelist
+    pub keeper: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetKeeperAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, keepers, keeper, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            keepers,
+            keeper,
+            signers,
+        })
+    }
+}
+
+/// `[is_keeper (u8)][fee_bps (u16)][limit_bps (u16)][reason (u8)?]`
+pub struct SetKeeperInstructionData {
+    /// Whether the key is whitelisted, `false` removing it
+    pub is_keeper: bool,
+    /// Fee of the keeper's loans (basis points)
+    pub fee_bps: u16,
+    /// Per-loan limit multiplier of the keeper, at least 10_000 (basis points)
+    pub limit_bps: u16,
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for SetKeeperInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 5 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let is_keeper = match data[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        let fee_bps = u16::from_le_bytes([data[1], data[2]]);
+        let limit_bps = u16::from_le_bytes([data[3], data[4]]);
+        if is_keeper && (fee_bps > 10_000 || limit_bps < 10_000) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            is_keeper,
+            fee_bps,
+            limit_bps,
+            reason: data.get(5).copied().unwrap_or(0),
+        })
+    }
+}
+
+impl SetKeeperInstructionData {
+    pub fn keeper(&self, key: &Pubkey) -> Option<Keeper> {
+        self.is_keeper.then_some(Keeper { key: *key, fee_bps: self.fee_bps, limit_bps: self.limit_bps })
+    }
+
+    /// Whether the change whitelists `key`, lowers its fee or raises its limit, in which case
+    /// it has to go through the timelock
+    pub fn loosens(&self, keepers: &KeeperWhitelist, key: &Pubkey) -> bool {
+        match (keepers.find(key), self.is_keeper) {
+            (_, false) => false,
+            (None, true) => true,
+            (Some(keeper), true) => self.fee_bps < keeper.fee_bps || self.limit_bps > keeper.limit_bps,
+        }
+    }
+
+    pub fn apply(&self, keepers: &mut KeeperWhitelist, key: &Pubkey) -> ProgramResult {
+        keepers.set(key, self.keeper(key))
+    }
+}
+
+/// records a keeper change in the audit log, emitting a KeeperUpdated event
+#[allow(clippy::too_many_arguments)]
+pub fn log_keeper_update(audit_log: &AccountInfo, pool: &Pubkey, actor: &Pubkey, key: &Pubkey, old: Option<&Keeper>, new: Option<&Keeper>, reason: u8, now: i64) -> ProgramResult {
+    append_audit_entry(audit_log, pool, &AuditEntry::new(
+        *actor, AuditAction::SetKeeper, *key, Keeper::audit_value(old), Keeper::audit_value(new), reason, now,
+    ))?;
+
+    KeeperUpdated {
+        authority: *actor,
+        keeper: *key,
+        is_keeper: new.is_some() as u8,
+        fee_bps: new.map_or(0, |keeper| keeper.fee_bps),
+        limit_bps: new.map_or(0, |keeper| keeper.limit_bps),
+    }.emit();
+
+    Ok(())
+}
+
+pub struct SetKeeper<'a> {
+    pub accounts: SetKeeperAccounts<'a>,
+    pub instruction_data: SetKeeperInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetKeeper<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SetKeeperAccounts::try_from(accounts)?;
+        let instruction_data = SetKeeperInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetKeeper<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &50;
+
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        let mut keepers = load_keeper_whitelist(self.accounts.keepers, self.accounts.emergency_account.key())?;
+        let key = self.accounts.keeper.key();
+
+        // Removing a keeper or raising its fee applies immediately, anything else is queued with QueueOperation
+        if self.instruction_data.loosens(&keepers, key) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let old_keeper = keepers.find(key).copied();
+        self.instruction_data.apply(&mut keepers, key)?;
+        save_keeper_whitelist(self.accounts.keepers, &keepers)?;
+
+        log_keeper_update(
+            self.accounts.audit_log,
+            self.accounts.emergency_account.key(),
+            self.accounts.admin.key(),
+            key,
+            old_keeper.as_ref(),
+            keepers.find(key),
+            self.instruction_data.reason,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+}
@@ -0,0 +1,140 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, load_emergency_state, save_emergency_state, verify_admin, AuditAction, AuditEntry, EmergencyState, Event,
+    PermissionedUpdated,
+};
+
+pub struct SetPermissionedAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetPermissionedAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            signers,
+        })
+    }
+}
+
+/// `[is_permissioned (u8)][reason (u8)?]`
+pub struct SetPermissionedInstructionData {
+    /// Whether only allowlisted borrowers can borrow
+    pub is_permissioned: bool,
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for SetPermissionedInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let is_permissioned = match data.first() {
+            Some(0) => false,
+            Some(1) => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self {
+            is_permissioned,
+            reason: data.get(1).copied().unwrap_or(0),
+        })
+    }
+}
+
+impl SetPermissionedInstructionData {
+    /// Whether the change opens a permissioned pool to every borrower, in which case it has to go through the timelock
+    pub fn loosens(&self, state: &EmergencyState) -> bool {
+        state.is_permissioned && !self.is_permissioned
+    }
+
+    pub fn apply(&self, state: &mut EmergencyState) {
+        state.is_permissioned = self.is_permissioned;
+    }
+}
+
+/// records a permissioned mode change in the audit log, emitting a PermissionedUpdated event
+pub fn log_permissioned_update(audit_log: &AccountInfo, pool: &Pubkey, actor: &Pubkey, old: &EmergencyState, new: &EmergencyState, reason: u8, now: i64) -> ProgramResult {
+    append_audit_entry(audit_log, pool, &AuditEntry::new(
+        *actor, AuditAction::SetPermissioned, Pubkey::default(), old.is_permissioned as u64, new.is_permissioned as u64, reason, now,
+    ))?;
+
+    PermissionedUpdated {
+        authority: *actor,
+        is_permissioned: new.is_permissioned as u8,
+    }.emit();
+
+    Ok(())
+}
+
+pub struct SetPermissioned<'a> {
+    pub accounts: SetPermissionedAccounts<'a>,
+    pub instruction_data: SetPermissionedInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetPermissioned<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SetPermissionedAccounts::try_from(accounts)?;
+        let instruction_data = SetPermissionedInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetPermissioned<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &52;
+
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        // Restricting the pool applies immediately, opening it is queued with QueueOperation
+        if self.instruction_data.loosens(&emergency_state) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let mut updated_state = emergency_state;
+        self.instruction_data.apply(&mut updated_state);
+        updated_state.last_updated = Clock::get()?.unix_timestamp;
+        save_emergency_state(self.accounts.emergency_account, &updated_state)?;
+
+        log_permissioned_update(
+            self.accounts.audit_log,
+            self.accounts.emergency_account.key(),
+            self.accounts.admin.key(),
+            &emergency_state,
+            &updated_state,
+            self.instruction_data.reason,
+            updated_state.last_updated,
+        )
+    }
+}
@@ -0,0 +1,166 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::{self, Pubkey},
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult
+};
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::{
+    append_audit_entry, load_borrower_allowlist, load_emergency_state, save_borrower_allowlist, verify_admin, AllowlistUpdated,
+    AuditAction, AuditEntry, BorrowerAllowlist, Event, ALLOWLIST_SEED, MAX_ALLOWED_BORROWERS,
+};
+
+pub struct UpdateAllowlistAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    /// Admin, paying for the allowlist on its first update
+    pub admin: &'a AccountInfo,
+    pub allowlist: &'a AccountInfo,
+    /// Borrower to add or remove
+    pub borrower: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for UpdateAllowlistAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, allowlist, borrower, system_program, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            allowlist,
+            borrower,
+            system_program,
+            signers,
+        })
+    }
+}
+
+/// `[is_allowed (u8)][reason (u8)?]`
+pub struct UpdateAllowlistInstructionData {
+    /// Whether the borrower is allowlisted, `false` removing it
+    pub is_allowed: bool,
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for UpdateAllowlistInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let is_allowed = match data.first() {
+            Some(0) => false,
+            Some(1) => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self {
+            is_allowed,
+            reason: data.get(1).copied().unwrap_or(0),
+        })
+    }
+}
+
+pub struct UpdateAllowlist<'a> {
+    pub accounts: UpdateAllowlistAccounts<'a>,
+    pub instruction_data: UpdateAllowlistInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for UpdateAllowlist<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = UpdateAllowlistAccounts::try_from(accounts)?;
+        let instruction_data = UpdateAllowlistInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> UpdateAllowlist<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &51;
+
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        let mut allowlist = self.load_allowlist()?;
+        let borrower = self.accounts.borrower.key();
+        let was_allowed = allowlist.is_allowed(borrower);
+        allowlist.set(borrower, self.instruction_data.is_allowed)?;
+        save_borrower_allowlist(self.accounts.allowlist, &allowlist)?;
+
+        append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+            *self.accounts.admin.key(),
+            AuditAction::UpdateAllowlist,
+            *borrower,
+            was_allowed as u64,
+            self.instruction_data.is_allowed as u64,
+            self.instruction_data.reason,
+            Clock::get()?.unix_timestamp,
+        ))?;
+
+        AllowlistUpdated {
+            authority: *self.accounts.admin.key(),
+            borrower: *borrower,
+            is_allowed: self.instruction_data.is_allowed as u8,
+        }.emit();
+
+        Ok(())
+    }
+
+    /// loads the pool's borrower allowlist, creating it at the admin's expense on its first update
+    fn load_allowlist(&self) -> Result<BorrowerAllowlist, ProgramError> {
+        let pool = self.accounts.emergency_account.key();
+        if self.accounts.allowlist.data_len() != 0 {
+            return load_borrower_allowlist(self.accounts.allowlist, pool);
+        }
+
+        let (allowlist_key, bump) = pubkey::find_program_address(&[ALLOWLIST_SEED, pool], &crate::ID);
+        if allowlist_key != *self.accounts.allowlist.key() {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let bump = [bump];
+        let signer_seeds = [
+            Seed::from(ALLOWLIST_SEED),
+            Seed::from(pool),
+            Seed::from(&bump),
+        ];
+        let signer_seeds = [Signer::from(&signer_seeds)];
+
+        let space = core::mem::size_of::<BorrowerAllowlist>();
+        CreateAccount {
+            from: self.accounts.admin,
+            to: self.accounts.allowlist,
+            lamports: Rent::get()?.minimum_balance(space),
+            space: space as u64,
+            owner: &crate::ID,
+        }.invoke_signed(&signer_seeds)?;
+
+        Ok(BorrowerAllowlist {
+            borrowers: [Pubkey::default(); MAX_ALLOWED_BORROWERS],
+            borrower_count: 0,
+            bump: bump[0],
+        })
+    }
+}
@@ -0,0 +1,101 @@
+use pinocchio::{
+    account_info::AccountInfo, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    sysvars::{clock::Clock, Sysvar}, 
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, load_emergency_state, save_emergency_state, AdminTransferred, AuditAction, AuditEntry, Event, FlashLoanError,
+    ADMIN_TRANSFER_DELAY,
+};
+
+pub struct AcceptAdminAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub pending_admin: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for AcceptAdminAccounts<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, pending_admin, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !pending_admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            pending_admin,
+        })
+    }
+}
+
+pub struct AcceptAdmin<'a> {
+    pub accounts: AcceptAdminAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for AcceptAdmin<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = AcceptAdminAccounts::try_from(accounts)?;
+        
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> AcceptAdmin<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &16;
+    
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+        
+        // Verify pending admin
+        if !emergency_state.has_pending_admin || emergency_state.pending_admin != *self.accounts.pending_admin.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // A transfer started before the multisig was enabled would hand over a key that can only pause
+        if emergency_state.admin_threshold != 0 {
+            return Err(FlashLoanError::MultisigActive.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        if current_timestamp < emergency_state.admin_transfer_timestamp + ADMIN_TRANSFER_DELAY {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        
+        // Complete the transfer
+        let mut updated_state = emergency_state;
+        updated_state.admin = emergency_state.pending_admin;
+        updated_state.has_pending_admin = false;
+        updated_state.pending_admin = Pubkey::default();
+        updated_state.last_updated = current_timestamp;
+
+        append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+            *self.accounts.pending_admin.key(),
+            AuditAction::AcceptAdmin,
+            emergency_state.admin,
+            0,
+            0,
+            0,
+            current_timestamp,
+        ))?;
+        
+        save_emergency_state(self.accounts.emergency_account, &updated_state)?;
+        
+        AdminTransferred {
+            old_admin: emergency_state.admin,
+            new_admin: updated_state.admin,
+        }.emit();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use pinocchio::{
+    account_info::AccountInfo, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    sysvars::{clock::Clock, Sysvar}, 
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, load_emergency_state, save_emergency_state, verify_admin, AuditAction, AuditEntry, DeadManSwitchUpdated, EmergencyMode,
+    EmergencyState, Event, HeartbeatRecorded,
+};
+
+pub struct HeartbeatAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for HeartbeatAccounts<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            signers,
+        })
+    }
+}
+
+pub struct HeartbeatInstructionData {
+    /// New dead-man switch configuration (interval, mode), left unchanged when omitted
+    pub dead_man_switch: Option<(i64, EmergencyMode)>,
+}
+
+impl TryFrom<&[u8]> for HeartbeatInstructionData {
+    type Error = ProgramError;
+    
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.is_empty() {
+            return Ok(Self { dead_man_switch: None });
+        }
+
+        if data.len() < 9 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let interval = i64::from_le_bytes(data[0..8].try_into().map_err(|_| ProgramError::InvalidInstructionData)?);
+        let mode = EmergencyMode::try_from(data[8])?;
+
+        // An interval of 0 disables the switch, which otherwise has to degrade to something
+        if interval < 0 || mode == EmergencyMode::Normal {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        
+        Ok(Self {
+            dead_man_switch: Some((interval, mode)),
+        })
+    }
+}
+
+impl HeartbeatInstructionData {
+    /// Whether the change disables the switch, lengthens its interval or lowers the mode it escalates to,
+    /// in which case it has to go through the timelock
+    pub fn loosens(&self, state: &EmergencyState) -> bool {
+        let Some((interval, mode)) = self.dead_man_switch else {
+            return false;
+        };
+
+        let later = state.heartbeat_interval != 0 && (interval == 0 || interval > state.heartbeat_interval);
+        later || (mode as u8) < (state.dead_man_mode as u8)
+    }
+
+    pub fn apply(&self, state: &mut EmergencyState) {
+        if let Some((interval, mode)) = self.dead_man_switch {
+            state.heartbeat_interval = interval;
+            state.dead_man_mode = mode;
+        }
+    }
+}
+
+/// records the dead-man switch settings that changed between `old` and `new` in the audit log,
+/// emitting a DeadManSwitchUpdated event
+pub fn log_dead_man_switch_update(audit_log: &AccountInfo, pool: &Pubkey, actor: &Pubkey, old: &EmergencyState, new: &EmergencyState, now: i64) -> ProgramResult {
+    if old.heartbeat_interval != new.heartbeat_interval {
+        append_audit_entry(audit_log, pool, &AuditEntry::new(
+            *actor, AuditAction::SetHeartbeatInterval, Pubkey::default(),
+            old.heartbeat_interval as u64, new.heartbeat_interval as u64, 0, now,
+        ))?;
+    }
+
+    if old.dead_man_mode != new.dead_man_mode {
+        append_audit_entry(audit_log, pool, &AuditEntry::new(
+            *actor, AuditAction::SetDeadManMode, Pubkey::default(),
+            old.dead_man_mode as u64, new.dead_man_mode as u64, 0, now,
+        ))?;
+    }
+
+    DeadManSwitchUpdated {
+        authority: *actor,
+        heartbeat_interval: new.heartbeat_interval,
+        dead_man_mode: new.dead_man_mode as u8,
+    }.emit();
+
+    Ok(())
+}
+
+pub struct Heartbeat<'a> {
+    pub accounts: HeartbeatAccounts<'a>,
+    pub instruction_data: HeartbeatInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Heartbeat<'a> {
+    type Error = ProgramError;
+    
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = HeartbeatAccounts::try_from(accounts)?;
+        let instruction_data = HeartbeatInstructionData::try_from(data)?;
+        
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Heartbeat<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &40;
+    
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+        
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+        
+        // Tightening the switch applies immediately, loosening it is queued with QueueOperation
+        if self.instruction_data.loosens(&emergency_state) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Record the heartbeat
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let mut updated_state = emergency_state;
+        updated_state.last_heartbeat = current_timestamp;
+        if self.instruction_data.dead_man_switch.is_some() {
+            self.instruction_data.apply(&mut updated_state);
+            updated_state.last_updated = current_timestamp;
+
+            log_dead_man_switch_update(
+                self.accounts.audit_log,
+                self.accounts.emergency_account.key(),
+                self.accounts.admin.key(),
+                &emergency_state,
+                &updated_state,
+                current_timestamp,
+            )?;
+        }
+        
+        save_emergency_state(self.accounts.emergency_account, &updated_state)?;
+        
+        HeartbeatRecorded {
+            admin: *self.accounts.admin.key(),
+            timestamp: current_timestamp,
+        }.emit();
+
+        Ok(())
+    }
+}
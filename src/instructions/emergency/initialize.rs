@@ -1,15 +1,22 @@
 use pinocchio::{
     account_info::AccountInfo, 
+    instruction::{Seed, Signer},
     program_error::ProgramError, 
-    pubkey::Pubkey, 
-    sysvars::{clock::Clock, Sysvar}, 
+    pubkey::{self, Pubkey}, 
+    sysvars::{clock::Clock, rent::Rent, Sysvar}, 
     ProgramResult
 };
+use pinocchio_system::instructions::CreateAccount;
 
-use crate::{EmergencyMode, EmergencyState};
+use crate::{
+    save_keeper_whitelist, AuditLogHeader, EmergencyInitialized, EmergencyMode, EmergencyState, Event, Keeper, KeeperWhitelist, AUDIT_SEED,
+    EMERGENCY_SEED, KEEPERS_SEED, MAX_ADMIN_SIGNERS, MAX_GUARDIANS, MAX_KEEPERS,
+};
 
 pub struct InitializeEmergencyAccounts<'a> {
     pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub keepers: &'a AccountInfo,
     pub admin: &'a AccountInfo,
     pub payer: &'a AccountInfo,
 }
@@ -18,12 +25,18 @@ impl<'a> TryFrom<&'a [AccountInfo]> for InitializeEmergencyAccounts<'a> {
     type Error = ProgramError;
     
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [emergency_account, admin, payer, ..] = accounts else {
+        let [emergency_account, audit_log, keepers, admin, payer, _system_program, ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
         Ok(Self {
             emergency_account,
+            audit_log,
+            keepers,
             admin,
             payer,
         })
@@ -31,37 +44,22 @@ impl<'a> TryFrom<&'a [AccountInfo]> for InitializeEmergencyAccounts<'a> {
 }
 
 pub struct InitializeEmergencyInstructionData {
-    pub max_loan_amount: u64,
-    pub max_total_outstanding: u64,
+    /// Id of the pool, `0` when omitted
+    pub pool_id: u64,
 }
 
 impl TryFrom<&[u8]> for InitializeEmergencyInstructionData {
     type Error = ProgramError;
     
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        if data.is_empty() {
-            // Use default values if no data provided
-            return Ok(Self {
-                max_loan_amount: 10_000_000_000,     // 10 SOL default
-                max_total_outstanding: 100_000_000_000, // 100 SOL default
-            });
-        }
-
-        if data.len() < 16 {
-            return Err(ProgramError::InvalidInstructionData);
-        }
-        
-        let max_loan_amount = u64::from_le_bytes(
-            data[0..8].try_into().map_err(|_| ProgramError::InvalidInstructionData)?
-        );
-        let max_total_outstanding = u64::from_le_bytes(
-            data[8..16].try_into().map_err(|_| ProgramError::InvalidInstructionData)?
-        );
+        // Loan limits are set per mint with RegisterReserve, a pool only needs its id
+        let pool_id = match data.get(0..8) {
+            Some(bytes) => u64::from_le_bytes(bytes.try_into().map_err(|_| ProgramError::InvalidInstructionData)?),
+            None if data.is_empty() => 0,
+            None => return Err(ProgramError::InvalidInstructionData),
+        };
         
-        Ok(Self {
-            max_loan_amount,
-            max_total_outstanding,
-        })
+        Ok(Self { pool_id })
     }
 }
 
@@ -88,18 +86,66 @@ impl<'a> InitializeEmergency<'a> {
     pub const DISCRIMINATOR: &'a u8 = &10;
     
     pub fn process(&self) -> ProgramResult {
+        // The emergency state is the pool's PDA so that loans can't be validated against a forged one,
+        // and every other account of the pool is derived from its key
+        let pool_id = self.instruction_data.pool_id.to_le_bytes();
+        let (emergency_key, bump) = pubkey::find_program_address(&[EMERGENCY_SEED, &pool_id], &crate::ID);
+        if emergency_key != *self.accounts.emergency_account.key() {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let bump = [bump];
+        let signer_seeds = [
+            Seed::from(EMERGENCY_SEED),
+            Seed::from(&pool_id),
+            Seed::from(&bump),
+        ];
+        let signer_seeds = [Signer::from(&signer_seeds)];
+
+        let space = core::mem::size_of::<EmergencyState>();
+        CreateAccount {
+            from: self.accounts.payer,
+            to: self.accounts.emergency_account,
+            lamports: Rent::get()?.minimum_balance(space),
+            space: space as u64,
+            owner: &crate::ID,
+        }.invoke_signed(&signer_seeds)?;
+
         let current_timestamp = Clock::get()?.unix_timestamp;
         
         let emergency_state = EmergencyState {
             is_paused: false,
             admin: *self.accounts.admin.key(),
-            max_loan_amount: self.instruction_data.max_loan_amount,
-            max_total_outstanding: self.instruction_data.max_total_outstanding,
             emergency_mode: EmergencyMode::Normal,
             last_updated: current_timestamp,
             has_pending_admin: false,
             pending_admin: Pubkey::default(),
             admin_transfer_timestamp: 0,
+            mode_limit_bps: EmergencyState::DEFAULT_MODE_LIMIT_BPS,
+            mode_fee_surcharge_bps: [0; 4],
+            paused_instructions: 0,
+            guardians: [Pubkey::default(); MAX_GUARDIANS],
+            guardian_count: 0,
+            admin_signers: [Pubkey::default(); MAX_ADMIN_SIGNERS],
+            admin_signer_count: 0,
+            admin_threshold: 0,
+            pause_nonces: [0; MAX_GUARDIANS],
+            pause_expiry: 0,
+            last_heartbeat: current_timestamp,
+            heartbeat_interval: 0,
+            dead_man_mode: EmergencyMode::Limited,
+            is_permissioned: false,
+            has_gate_program: false,
+            gate_program: Pubkey::default(),
+            referral_share_bps: 0,
+            has_treasury: false,
+            treasury: Pubkey::default(),
+            sweep_when_frozen: false,
+            pool_id: self.instruction_data.pool_id,
+            has_recovery: false,
+            recovery: Pubkey::default(),
+            mode_changed_at: current_timestamp,
+            pause_nonce_floor: 0,
         };
         
         let mut emergency_data = self.accounts.emergency_account.try_borrow_mut_data()?;
@@ -110,7 +156,79 @@ impl<'a> InitializeEmergency<'a> {
             )
         };
         emergency_data[..core::mem::size_of::<EmergencyState>()].copy_from_slice(emergency_state_bytes);
+        drop(emergency_data);
+
+        // The audit log records every admin action from now on
+        let (audit_key, audit_bump) = pubkey::find_program_address(&[AUDIT_SEED, &emergency_key], &crate::ID);
+        if audit_key != *self.accounts.audit_log.key() {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let audit_bump = [audit_bump];
+        let signer_seeds = [
+            Seed::from(AUDIT_SEED),
+            Seed::from(&emergency_key),
+            Seed::from(&audit_bump),
+        ];
+        let signer_seeds = [Signer::from(&signer_seeds)];
+
+        CreateAccount {
+            from: self.accounts.payer,
+            to: self.accounts.audit_log,
+            lamports: Rent::get()?.minimum_balance(AuditLogHeader::ACCOUNT_LEN),
+            space: AuditLogHeader::ACCOUNT_LEN as u64,
+            owner: &crate::ID,
+        }.invoke_signed(&signer_seeds)?;
+
+        let header = AuditLogHeader {
+            count: 0,
+            bump: audit_bump[0],
+        };
+
+        let mut audit_data = self.accounts.audit_log.try_borrow_mut_data()?;
+        let header_bytes = unsafe {
+            core::slice::from_raw_parts(
+                &header as *const AuditLogHeader as *const u8,
+                AuditLogHeader::LEN,
+            )
+        };
+        audit_data[..AuditLogHeader::LEN].copy_from_slice(header_bytes);
+        drop(audit_data);
+
+        // Keepers are whitelisted later with SetKeeper
+        let (keepers_key, keepers_bump) = pubkey::find_program_address(&[KEEPERS_SEED, &emergency_key], &crate::ID);
+        if keepers_key != *self.accounts.keepers.key() {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let keepers_bump = [keepers_bump];
+        let signer_seeds = [
+            Seed::from(KEEPERS_SEED),
+            Seed::from(&emergency_key),
+            Seed::from(&keepers_bump),
+        ];
+        let signer_seeds = [Signer::from(&signer_seeds)];
+
+        let space = core::mem::size_of::<KeeperWhitelist>();
+        CreateAccount {
+            from: self.accounts.payer,
+            to: self.accounts.keepers,
+            lamports: Rent::get()?.minimum_balance(space),
+            space: space as u64,
+            owner: &crate::ID,
+        }.invoke_signed(&signer_seeds)?;
+
+        save_keeper_whitelist(self.accounts.keepers, &KeeperWhitelist {
+            keepers: [Keeper { key: Pubkey::default(), fee_bps: 0, limit_bps: 0 }; MAX_KEEPERS],
+            keeper_count: 0,
+            bump: keepers_bump[0],
+        })?;
         
+        EmergencyInitialized {
+            admin: *self.accounts.admin.key(),
+            pool_id: self.instruction_data.pool_id,
+        }.emit();
+
         Ok(())
     }
 }
@@ -2,8 +2,24 @@ pub mod set_mode;
 pub mod initialize;
 pub mod pause;
 pub mod unpause;
+pub mod update_limits;
+pub mod set_guardians;
+pub mod set_admin_multisig;
+pub mod transfer_admin;
+pub mod accept_admin;
+pub mod pause_with_signature;
+pub mod heartbeat;
+pub mod trigger_dead_man_switch;
 
 pub use set_mode::*;
 pub use initialize::*;
 pub use pause::*;
-pub use unpause::*;
\ No newline at end of file
+pub use unpause::*;
+pub use update_limits::*;
+pub use set_guardians::*;
+pub use set_admin_multisig::*;
+pub use transfer_admin::*;
+pub use accept_admin::*;
+pub use pause_with_signature::*;
+pub use heartbeat::*;
+pub use trigger_dead_man_switch::*;
\ No newline at end of file
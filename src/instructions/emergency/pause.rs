@@ -1,43 +1,119 @@
 use pinocchio::{
     account_info::AccountInfo, 
     program_error::ProgramError, 
+    pubkey::Pubkey, 
     sysvars::{clock::Clock, Sysvar}, 
     ProgramResult
 };
 
-use crate::EmergencyState;
+use crate::{
+    append_audit_entry, load_emergency_state, load_reserve, pause_bits, save_emergency_state, save_reserve, AuditAction, AuditEntry, Event,
+    Paused, GUARDIAN_PAUSE_DURATION,
+};
 
 pub struct PauseAccounts<'a> {
     pub emergency_account: &'a AccountInfo,
-    pub admin: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    /// (reserve, mint) when pausing a single mint
+    pub reserve: Option<(&'a AccountInfo, &'a AccountInfo)>,
 }
 
-impl<'a> TryFrom<&'a [AccountInfo]> for PauseAccounts<'a> {
+impl<'a> TryFrom<(&'a [AccountInfo], bool)> for PauseAccounts<'a> {
     type Error = ProgramError;
     
-    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [emergency_account, admin, ..] = accounts else {
+    fn try_from((accounts, per_mint): (&'a [AccountInfo], bool)) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, authority, rest @ ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // The reserve and mint follow the authority only when the instruction data scopes the pause to a mint
+        let reserve = match rest {
+            [reserve, mint, ..] if per_mint => Some((reserve, mint)),
+            _ if per_mint => return Err(ProgramError::NotEnoughAccountKeys),
+            _ => None,
+        };
+
         Ok(Self {
             emergency_account,
-            admin,
+            audit_log,
+            authority,
+            reserve,
+        })
+    }
+}
+
+/// `[per_mint (u8)][instructions (u16)][duration (i64)?][reason (u8)?]`, empty data pausing everything protocol-wide
+pub struct PauseInstructionData {
+    /// Whether the pause targets the mint of the reserve passed after the authority
+    pub per_mint: bool,
+    /// Instruction flags to pause, `0` pausing everything
+    pub instructions: u16,
+    /// Seconds until the pause lifts itself, `0` pausing until unpaused
+    pub duration: i64,
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for PauseInstructionData {
+    type Error = ProgramError;
+    
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.is_empty() {
+            return Ok(Self { per_mint: false, instructions: 0, duration: 0, reason: 0 });
+        }
+
+        if data.len() < 3 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let per_mint = match data[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        let data = &data[1..];
+
+        let (duration, reason) = match data.get(2..10) {
+            Some(duration) => (
+                i64::from_le_bytes(duration.try_into().map_err(|_| ProgramError::InvalidInstructionData)?),
+                data.get(10).copied().unwrap_or(0),
+            ),
+            None => (0, data.get(2).copied().unwrap_or(0)),
+        };
+        if duration < 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        
+        Ok(Self {
+            per_mint,
+            instructions: u16::from_le_bytes([data[0], data[1]]),
+            duration,
+            reason,
         })
     }
 }
 
 pub struct Pause<'a> {
     pub accounts: PauseAccounts<'a>,
+    pub instruction_data: PauseInstructionData,
 }
 
-impl<'a> TryFrom<&'a [AccountInfo]> for Pause<'a> {
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Pause<'a> {
     type Error = ProgramError;
     
-    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let accounts = PauseAccounts::try_from(accounts)?;
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = PauseInstructionData::try_from(data)?;
+        let accounts = PauseAccounts::try_from((accounts, instruction_data.per_mint))?;
         
-        Ok(Self { accounts })
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
     }
 }
 
@@ -45,33 +121,67 @@ impl<'a> Pause<'a> {
     pub const DISCRIMINATOR: &'a u8 = &11;
     
     pub fn process(&self) -> ProgramResult {
-        // Load emergency state
-        let emergency_data = self.accounts.emergency_account.try_borrow_data()?;
-        let emergency_state = unsafe {
-            *(emergency_data.as_ptr() as *const EmergencyState)
-        };
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
         
-        // Verify admin authority
-        if emergency_state.admin != *self.accounts.admin.key() {
+        // Verify admin or guardian authority, pausing only escalates so a single admin signer is enough
+        let authority = self.accounts.authority.key();
+        let is_admin = emergency_state.admin == *authority || emergency_state.is_admin_signer(authority);
+        if !is_admin && !emergency_state.is_guardian(authority) {
             return Err(ProgramError::InvalidAccountData);
         }
+
+        // Guardian pauses always expire, lasting until the admin ratifies them at most
+        let duration = match self.instruction_data.duration {
+            duration if is_admin => duration,
+            0 => GUARDIAN_PAUSE_DURATION,
+            duration => duration.min(GUARDIAN_PAUSE_DURATION),
+        };
+
+        let instructions = self.instruction_data.instructions;
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let expiry = if duration == 0 { 0 } else { current_timestamp + duration };
+
+        // Pause a single mint, expiring and ratified like the protocol-wide pause
+        if let Some((reserve_account, mint)) = self.accounts.reserve {
+            let mut reserve = load_reserve(reserve_account, self.accounts.emergency_account.key(), mint.key())?;
+            let old_value = reserve.paused_instructions as u64;
+            reserve.pause(instructions, expiry, current_timestamp);
+            save_reserve(reserve_account, &reserve)?;
+
+            append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+                *authority, AuditAction::Pause, *mint.key(), old_value, reserve.paused_instructions as u64,
+                self.instruction_data.reason, current_timestamp,
+            ))?;
+
+            Paused { authority: *authority, mint: *mint.key(), instructions, expiry: reserve.pause_expiry }.emit();
+            return Ok(());
+        }
         
         // Update state to paused
-        drop(emergency_data);
         let mut updated_state = emergency_state;
-        updated_state.is_paused = true;
-        updated_state.last_updated = Clock::get()?.unix_timestamp;
+        updated_state.pause(instructions, expiry, current_timestamp);
+        updated_state.last_updated = current_timestamp;
+
+        append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+            *authority,
+            AuditAction::Pause,
+            Pubkey::default(),
+            pause_bits(emergency_state.is_paused, emergency_state.paused_instructions),
+            pause_bits(updated_state.is_paused, updated_state.paused_instructions),
+            self.instruction_data.reason,
+            current_timestamp,
+        ))?;
         
-        // Write updated state
-        let mut emergency_data = self.accounts.emergency_account.try_borrow_mut_data()?;
-        let updated_state_bytes = unsafe {
-            core::slice::from_raw_parts(
-                &updated_state as *const EmergencyState as *const u8,
-                core::mem::size_of::<EmergencyState>(),
-            )
-        };
-        emergency_data[..core::mem::size_of::<EmergencyState>()].copy_from_slice(updated_state_bytes);
+        save_emergency_state(self.accounts.emergency_account, &updated_state)?;
         
+        Paused {
+            authority: *authority,
+            mint: Pubkey::default(),
+            instructions,
+            expiry: updated_state.pause_expiry,
+        }.emit();
+
         Ok(())
     }
 }
@@ -0,0 +1,212 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, instructions::Instructions, Sysvar},
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, load_emergency_state, load_reserve, pause_bits, save_emergency_state, save_reserve, AuditAction, AuditEntry, Event,
+    Paused, GUARDIAN_PAUSE_DURATION,
+};
+
+/// Ed25519 signature verification precompile
+pub const ED25519_PROGRAM_ID: Pubkey = pinocchio_pubkey::pubkey!("Ed25519SigVerify111111111111111111111111111");
+
+/// Reads the single signature verified by an Ed25519 precompile instruction, returning the
+/// public key and the signed message. Every offset must point into the precompile instruction
+/// itself, otherwise the verified bytes could live in another instruction of the transaction.
+pub fn read_ed25519_signature(data: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
+    // num_signatures (1 byte), padding (1 byte), then a single 14 bytes offsets entry
+    if data.len() < 16 || data[0] != 1 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let offset = |i: usize| u16::from_le_bytes([data[2 + i * 2], data[3 + i * 2]]);
+    let [_signature_offset, signature_index, public_key_offset, public_key_index, message_offset, message_size, message_index] =
+        [0, 1, 2, 3, 4, 5, 6].map(offset);
+
+    if signature_index != u16::MAX || public_key_index != u16::MAX || message_index != u16::MAX {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let public_key: Pubkey = data
+        .get(public_key_offset as usize..public_key_offset as usize + 32)
+        .ok_or(ProgramError::InvalidInstructionData)?
+        .try_into()
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let message = data
+        .get(message_offset as usize..message_offset as usize + message_size as usize)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    Ok((public_key, message))
+}
+
+/// Pause message signed offchain by a guardian:
+/// `PREFIX || pool || nonce (u64) || expiry (i64) || instruction flags (u16) || mint`,
+/// an all zero mint pausing the whole pool
+pub struct PauseMessage {
+    /// Emergency state PDA of the pool to pause
+    pub pool: Pubkey,
+    pub nonce: u64,
+    pub expiry: i64,
+    pub instructions: u16,
+    pub mint: Pubkey,
+}
+
+impl PauseMessage {
+    pub const PREFIX: &'static [u8] = b"flash_loan:pause";
+    pub const LEN: usize = 16 + 32 + 8 + 8 + 2 + 32;
+}
+
+impl TryFrom<&[u8]> for PauseMessage {
+    type Error = ProgramError;
+
+    fn try_from(message: &[u8]) -> Result<Self, Self::Error> {
+        if message.len() != Self::LEN || !message.starts_with(Self::PREFIX) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            pool: message[16..48].try_into().map_err(|_| ProgramError::InvalidInstructionData)?,
+            nonce: u64::from_le_bytes(message[48..56].try_into().map_err(|_| ProgramError::InvalidInstructionData)?),
+            expiry: i64::from_le_bytes(message[56..64].try_into().map_err(|_| ProgramError::InvalidInstructionData)?),
+            instructions: u16::from_le_bytes([message[64], message[65]]),
+            mint: message[66..98].try_into().map_err(|_| ProgramError::InvalidInstructionData)?,
+        })
+    }
+}
+
+pub struct PauseWithSignatureAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub instruction_sysvar: &'a AccountInfo,
+    /// (reserve, mint) when the message pauses a single mint
+    pub reserve: Option<(&'a AccountInfo, &'a AccountInfo)>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for PauseWithSignatureAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        // No signer required, any relayer can submit a guardian's signed message
+        let [emergency_account, audit_log, instruction_sysvar, rest @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let reserve = match rest {
+            [reserve, mint, ..] => Some((reserve, mint)),
+            _ => None,
+        };
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            instruction_sysvar,
+            reserve,
+        })
+    }
+}
+
+pub struct PauseWithSignature<'a> {
+    pub accounts: PauseWithSignatureAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for PauseWithSignature<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = PauseWithSignatureAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> PauseWithSignature<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &19;
+
+    pub fn process(&self) -> ProgramResult {
+        // Introspecting the Ed25519 instruction right before this one
+        let instruction_sysvar = Instructions::try_from(self.accounts.instruction_sysvar)?;
+        let instruction = instruction_sysvar.get_instruction_relative(-1)?;
+
+        if instruction.get_program_id() != &ED25519_PROGRAM_ID {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let (guardian, message) = read_ed25519_signature(instruction.get_instruction_data())?;
+        let message = PauseMessage::try_from(message)?;
+        drop(instruction_sysvar);
+
+        // Messages signed for another pool or deployment can't be replayed here
+        if message.pool != *self.accounts.emergency_account.key() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+
+        // Verify guardian authority
+        let slot = emergency_state.guardian_slot(&guardian).ok_or(ProgramError::InvalidAccountData)?;
+
+        // Replay protection, each message is accepted once and only until it expires. Nonces are
+        // tracked per guardian, so that a guardian's messages never invalidate another's pre-signed ones
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        if message.nonce <= emergency_state.pause_nonces[slot] || current_timestamp > message.expiry {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let mut updated_state = emergency_state;
+        updated_state.consume_pause_nonce(slot, message.nonce);
+        updated_state.last_updated = current_timestamp;
+
+        let instructions = message.instructions;
+        let expiry;
+
+        if message.mint != Pubkey::default() {
+            // Pause a single mint
+            let Some((reserve_account, mint)) = self.accounts.reserve else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            if *mint.key() != message.mint {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            // Like any guardian pause, it lifts unless the admin ratifies it
+            let mut reserve = load_reserve(reserve_account, self.accounts.emergency_account.key(), mint.key())?;
+            let old_value = reserve.paused_instructions as u64;
+            reserve.pause(instructions, current_timestamp + GUARDIAN_PAUSE_DURATION, current_timestamp);
+            save_reserve(reserve_account, &reserve)?;
+            expiry = reserve.pause_expiry;
+
+            append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+                guardian, AuditAction::Pause, message.mint, old_value, reserve.paused_instructions as u64, 0, current_timestamp,
+            ))?;
+        } else {
+            // Like any guardian pause, it lifts unless the admin ratifies it
+            updated_state.pause(instructions, current_timestamp + GUARDIAN_PAUSE_DURATION, current_timestamp);
+            expiry = updated_state.pause_expiry;
+
+            append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+                guardian,
+                AuditAction::Pause,
+                Pubkey::default(),
+                pause_bits(emergency_state.is_paused, emergency_state.paused_instructions),
+                pause_bits(updated_state.is_paused, updated_state.paused_instructions),
+                0,
+                current_timestamp,
+            ))?;
+        }
+
+        save_emergency_state(self.accounts.emergency_account, &updated_state)?;
+
+        Paused {
+            authority: guardian,
+            mint: message.mint,
+            instructions,
+            expiry,
+        }.emit();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use pinocchio::{
+    account_info::AccountInfo, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    sysvars::{clock::Clock, Sysvar}, 
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, load_emergency_state, save_emergency_state, verify_admin, AdminMultisigUpdated, AuditAction, AuditEntry, Event,
+    MAX_ADMIN_SIGNERS,
+};
+
+pub struct SetAdminMultisigAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetAdminMultisigAccounts<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            signers,
+        })
+    }
+}
+
+pub struct SetAdminMultisigInstructionData {
+    pub threshold: u8,
+    pub admin_signers: [Pubkey; MAX_ADMIN_SIGNERS],
+    pub admin_signer_count: u8,
+}
+
+impl TryFrom<&[u8]> for SetAdminMultisigInstructionData {
+    type Error = ProgramError;
+    
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let [threshold, admin_signer_count, data @ ..] = data else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+        
+        if *admin_signer_count as usize > MAX_ADMIN_SIGNERS || data.len() < *admin_signer_count as usize * 32 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // A threshold of 0 hands the authority back to the admin key alone
+        if threshold > admin_signer_count {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let mut admin_signers = [Pubkey::default(); MAX_ADMIN_SIGNERS];
+        for (i, signer) in admin_signers.iter_mut().take(*admin_signer_count as usize).enumerate() {
+            *signer = data[i * 32..(i + 1) * 32].try_into().map_err(|_| ProgramError::InvalidInstructionData)?;
+        }
+
+        // Duplicated signers would let a single key count twice toward the threshold
+        for i in 1..*admin_signer_count as usize {
+            if admin_signers[..i].contains(&admin_signers[i]) {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+        }
+        
+        Ok(Self {
+            threshold: *threshold,
+            admin_signers,
+            admin_signer_count: *admin_signer_count,
+        })
+    }
+}
+
+pub struct SetAdminMultisig<'a> {
+    pub accounts: SetAdminMultisigAccounts<'a>,
+    pub instruction_data: SetAdminMultisigInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetAdminMultisig<'a> {
+    type Error = ProgramError;
+    
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SetAdminMultisigAccounts::try_from(accounts)?;
+        let instruction_data = SetAdminMultisigInstructionData::try_from(data)?;
+        
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetAdminMultisig<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &18;
+    
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+        
+        // Verify admin authority, the current multisig approves its replacement
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+        
+        // Replace the admin multisig
+        let mut updated_state = emergency_state;
+        updated_state.admin_signers = self.instruction_data.admin_signers;
+        updated_state.admin_signer_count = self.instruction_data.admin_signer_count;
+        updated_state.admin_threshold = self.instruction_data.threshold;
+        updated_state.last_updated = Clock::get()?.unix_timestamp;
+
+        append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+            *self.accounts.admin.key(),
+            AuditAction::SetAdminMultisig,
+            Pubkey::default(),
+            (emergency_state.admin_threshold as u64) << 8 | emergency_state.admin_signer_count as u64,
+            (updated_state.admin_threshold as u64) << 8 | updated_state.admin_signer_count as u64,
+            0,
+            updated_state.last_updated,
+        ))?;
+        
+        save_emergency_state(self.accounts.emergency_account, &updated_state)?;
+        
+        AdminMultisigUpdated {
+            authority: *self.accounts.admin.key(),
+            threshold: updated_state.admin_threshold,
+            signer_count: updated_state.admin_signer_count,
+        }.emit();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use pinocchio::{
+    account_info::AccountInfo, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    sysvars::{clock::Clock, Sysvar}, 
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, load_emergency_state, save_emergency_state, verify_admin, AuditAction, AuditEntry, Event, GuardiansUpdated,
+    MAX_GUARDIANS,
+};
+
+pub struct SetGuardiansAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetGuardiansAccounts<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            signers,
+        })
+    }
+}
+
+pub struct SetGuardiansInstructionData {
+    pub guardians: [Pubkey; MAX_GUARDIANS],
+    pub guardian_count: u8,
+}
+
+impl TryFrom<&[u8]> for SetGuardiansInstructionData {
+    type Error = ProgramError;
+    
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let (guardian_count, data) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+        
+        if *guardian_count as usize > MAX_GUARDIANS || data.len() < *guardian_count as usize * 32 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let mut guardians = [Pubkey::default(); MAX_GUARDIANS];
+        for (i, guardian) in guardians.iter_mut().take(*guardian_count as usize).enumerate() {
+            *guardian = data[i * 32..(i + 1) * 32].try_into().map_err(|_| ProgramError::InvalidInstructionData)?;
+        }
+        
+        Ok(Self {
+            guardians,
+            guardian_count: *guardian_count,
+        })
+    }
+}
+
+pub struct SetGuardians<'a> {
+    pub accounts: SetGuardiansAccounts<'a>,
+    pub instruction_data: SetGuardiansInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetGuardians<'a> {
+    type Error = ProgramError;
+    
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SetGuardiansAccounts::try_from(accounts)?;
+        let instruction_data = SetGuardiansInstructionData::try_from(data)?;
+        
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetGuardians<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &17;
+    
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+        
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+        
+        // Replace the guardian set
+        let mut updated_state = emergency_state;
+        updated_state.replace_guardians(self.instruction_data.guardians, self.instruction_data.guardian_count);
+        updated_state.last_updated = Clock::get()?.unix_timestamp;
+
+        append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+            *self.accounts.admin.key(),
+            AuditAction::SetGuardians,
+            Pubkey::default(),
+            emergency_state.guardian_count as u64,
+            updated_state.guardian_count as u64,
+            0,
+            updated_state.last_updated,
+        ))?;
+        
+        save_emergency_state(self.accounts.emergency_account, &updated_state)?;
+        
+        GuardiansUpdated {
+            authority: *self.accounts.admin.key(),
+            guardian_count: updated_state.guardian_count,
+        }.emit();
+
+        Ok(())
+    }
+}
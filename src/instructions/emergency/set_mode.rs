@@ -1,51 +1,69 @@
 use pinocchio::{
     account_info::AccountInfo, 
     program_error::ProgramError, 
+    pubkey::Pubkey, 
     sysvars::{clock::Clock, Sysvar}, 
     ProgramResult
 };
 
-use crate::{EmergencyMode, EmergencyState};
+use crate::{
+    append_audit_entry, load_emergency_state, save_emergency_state, verify_admin, AuditAction, AuditEntry, EmergencyMode, EmergencyState,
+    Event, ModeChanged,
+};
 
 pub struct SetEmergencyModeAccounts<'a> {
     pub emergency_account: &'a AccountInfo,
-    pub admin: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
 }
 
 impl<'a> TryFrom<&'a [AccountInfo]> for SetEmergencyModeAccounts<'a> {
     type Error = ProgramError;
     
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [emergency_account, admin, ..] = accounts else {
+        let [emergency_account, audit_log, authority, signers @ ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
         Ok(Self {
             emergency_account,
-            admin,
+            audit_log,
+            authority,
+            signers,
         })
     }
 }
 
 pub struct SetEmergencyModeInstructionData {
     pub mode: EmergencyMode,
+    /// Reason code recorded in the audit log
+    pub reason: u8,
 }
 
 impl TryFrom<&[u8]> for SetEmergencyModeInstructionData {
     type Error = ProgramError;
     
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        let mode_byte = data.get(0).ok_or(ProgramError::InvalidInstructionData)?;
-        
-        let mode = match *mode_byte {
-            0 => EmergencyMode::Normal,
-            1 => EmergencyMode::Limited,
-            2 => EmergencyMode::Emergency,
-            3 => EmergencyMode::Frozen,
-            _ => return Err(ProgramError::InvalidInstructionData),
-        };
+        let mode_byte = data.first().ok_or(ProgramError::InvalidInstructionData)?;
+        let mode = EmergencyMode::try_from(*mode_byte)?;
         
-        Ok(Self { mode })
+        Ok(Self {
+            mode,
+            reason: data.get(1).copied().unwrap_or(0),
+        })
+    }
+}
+
+impl SetEmergencyModeInstructionData {
+    /// Whether the change de-escalates the emergency mode
+    pub fn loosens(&self, emergency_state: &EmergencyState) -> bool {
+        (self.mode as u8) < (emergency_state.emergency_mode as u8)
     }
 }
 
@@ -72,34 +90,49 @@ impl<'a> SetEmergencyMode<'a> {
     pub const DISCRIMINATOR: &'a u8 = &13;
     
     pub fn process(&self) -> ProgramResult {
-        // Load emergency state
-        let emergency_data = self.accounts.emergency_account.try_borrow_data()?;
-        let emergency_state = unsafe {
-            *(emergency_data.as_ptr() as *const EmergencyState)
-        };
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
         
-        // Verify admin authority
-        if emergency_state.admin != *self.accounts.admin.key() {
-            return Err(ProgramError::InvalidAccountData);
+        // Verify admin authority, guardians can only move toward Frozen
+        if verify_admin(&emergency_state, self.accounts.authority, self.accounts.signers).is_err() {
+            if !emergency_state.is_guardian(self.accounts.authority.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            if (self.instruction_data.mode as u8) <= (emergency_state.emergency_mode as u8) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        // The admin de-escalates through the timelock, queued with QueueOperation
+        if self.instruction_data.loosens(&emergency_state) {
+            return Err(ProgramError::InvalidInstructionData);
         }
         
-        // Update emergency mode
-        drop(emergency_data);
         let clock = Clock::get()?;
+        emergency_state.check_mode_transition(self.instruction_data.mode, clock.unix_timestamp)?;
+        
+        // Update emergency mode
         let mut updated_state = emergency_state;
-        updated_state.emergency_mode = self.instruction_data.mode;
+        updated_state.set_mode(self.instruction_data.mode, clock.unix_timestamp);
         updated_state.last_updated = clock.unix_timestamp;
+
+        append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+            *self.accounts.authority.key(),
+            AuditAction::SetEmergencyMode,
+            Pubkey::default(),
+            emergency_state.emergency_mode as u64,
+            updated_state.emergency_mode as u64,
+            self.instruction_data.reason,
+            clock.unix_timestamp,
+        ))?;
         
-        // Write updated state
-        let mut emergency_data = self.accounts.emergency_account.try_borrow_mut_data()?;
-        let updated_state_bytes = unsafe {
-            core::slice::from_raw_parts(
-                &updated_state as *const EmergencyState as *const u8,
-                core::mem::size_of::<EmergencyState>(),
-            )
-        };
-        emergency_data[..core::mem::size_of::<EmergencyState>()].copy_from_slice(updated_state_bytes);
+        save_emergency_state(self.accounts.emergency_account, &updated_state)?;
         
+        ModeChanged {
+            authority: *self.accounts.authority.key(),
+            old_mode: emergency_state.emergency_mode as u8,
+            new_mode: updated_state.emergency_mode as u8,
+        }.emit();
+
         Ok(())
     }
 }
@@ -0,0 +1,124 @@
+use pinocchio::{
+    account_info::AccountInfo, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    sysvars::{clock::Clock, Sysvar}, 
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, load_emergency_state, save_emergency_state, verify_admin, AdminTransferStarted, AuditAction, AuditEntry, Event,
+    FlashLoanError,
+};
+
+pub struct TransferAdminAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for TransferAdminAccounts<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            signers,
+        })
+    }
+}
+
+pub struct TransferAdminInstructionData {
+    pub new_admin: Pubkey,
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for TransferAdminInstructionData {
+    type Error = ProgramError;
+    
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 32 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        
+        Ok(Self {
+            new_admin: data[0..32].try_into().map_err(|_| ProgramError::InvalidInstructionData)?,
+            reason: data.get(32).copied().unwrap_or(0),
+        })
+    }
+}
+
+pub struct TransferAdmin<'a> {
+    pub accounts: TransferAdminAccounts<'a>,
+    pub instruction_data: TransferAdminInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for TransferAdmin<'a> {
+    type Error = ProgramError;
+    
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = TransferAdminAccounts::try_from(accounts)?;
+        let instruction_data = TransferAdminInstructionData::try_from(data)?;
+        
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> TransferAdmin<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &15;
+    
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+        
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+        
+        // Once the multisig is active the admin key can only pause, the signer set is changed with SetAdminMultisig instead
+        if emergency_state.admin_threshold != 0 {
+            return Err(FlashLoanError::MultisigActive.into());
+        }
+
+        // Start the transfer, the new admin accepts it once the delay has passed
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let mut updated_state = emergency_state;
+        updated_state.has_pending_admin = true;
+        updated_state.pending_admin = self.instruction_data.new_admin;
+        updated_state.admin_transfer_timestamp = current_timestamp;
+        updated_state.last_updated = current_timestamp;
+
+        append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+            *self.accounts.admin.key(),
+            AuditAction::TransferAdmin,
+            self.instruction_data.new_admin,
+            0,
+            0,
+            self.instruction_data.reason,
+            current_timestamp,
+        ))?;
+        
+        save_emergency_state(self.accounts.emergency_account, &updated_state)?;
+        
+        AdminTransferStarted {
+            admin: *self.accounts.admin.key(),
+            pending_admin: self.instruction_data.new_admin,
+        }.emit();
+
+        Ok(())
+    }
+}
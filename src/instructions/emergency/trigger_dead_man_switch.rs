@@ -0,0 +1,83 @@
+use pinocchio::{
+    account_info::AccountInfo, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    sysvars::{clock::Clock, Sysvar}, 
+    ProgramResult
+};
+
+use crate::{append_audit_entry, load_emergency_state, save_emergency_state, AuditAction, AuditEntry, Event, FlashLoanError, ModeChanged};
+
+pub struct TriggerDeadManSwitchAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for TriggerDeadManSwitchAccounts<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        // No signer required, anyone can crank the switch once the admin went silent
+        let [emergency_account, audit_log, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+        })
+    }
+}
+
+pub struct TriggerDeadManSwitch<'a> {
+    pub accounts: TriggerDeadManSwitchAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for TriggerDeadManSwitch<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = TriggerDeadManSwitchAccounts::try_from(accounts)?;
+        
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> TriggerDeadManSwitch<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &41;
+    
+    pub fn process(&self) -> ProgramResult {
+        let mut emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        if !emergency_state.is_heartbeat_expired(current_timestamp) {
+            return Err(FlashLoanError::HeartbeatNotExpired.into());
+        }
+
+        // Only ever escalates, a mode already at least as severe is kept
+        if (emergency_state.dead_man_mode as u8) <= (emergency_state.emergency_mode as u8) {
+            return Err(FlashLoanError::ModeUnchanged.into());
+        }
+
+        append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+            Pubkey::default(),
+            AuditAction::DeadManSwitch,
+            Pubkey::default(),
+            emergency_state.emergency_mode as u64,
+            emergency_state.dead_man_mode as u64,
+            0,
+            current_timestamp,
+        ))?;
+
+        ModeChanged {
+            authority: Pubkey::default(),
+            old_mode: emergency_state.emergency_mode as u8,
+            new_mode: emergency_state.dead_man_mode as u8,
+        }.emit();
+
+        emergency_state.set_mode(emergency_state.dead_man_mode, current_timestamp);
+        emergency_state.last_updated = current_timestamp;
+
+        save_emergency_state(self.accounts.emergency_account, &emergency_state)
+    }
+}
@@ -1,43 +1,108 @@
 use pinocchio::{
     account_info::AccountInfo, 
     program_error::ProgramError, 
+    pubkey::Pubkey, 
     sysvars::{clock::Clock, Sysvar}, 
     ProgramResult
 };
 
-use crate::EmergencyState;
+use crate::{
+    append_audit_entry, load_emergency_state, load_reserve, pause_bits, save_emergency_state, save_reserve, verify_admin, AuditAction,
+    AuditEntry, Event, Unpaused,
+};
 
 pub struct UnpauseAccounts<'a> {
     pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
     pub admin: &'a AccountInfo,
+    /// (reserve, mint) when unpausing a single mint
+    pub reserve: Option<(&'a AccountInfo, &'a AccountInfo)>,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
 }
 
-impl<'a> TryFrom<&'a [AccountInfo]> for UnpauseAccounts<'a> {
+impl<'a> TryFrom<(&'a [AccountInfo], bool)> for UnpauseAccounts<'a> {
     type Error = ProgramError;
     
-    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [emergency_account, admin, ..] = accounts else {
+    fn try_from((accounts, per_mint): (&'a [AccountInfo], bool)) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, rest @ ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // The reserve and mint follow the admin only when the instruction data scopes the unpause to a mint,
+        // as with Pause, the multisig signers coming last
+        let (reserve, signers) = match rest {
+            [reserve, mint, signers @ ..] if per_mint => (Some((reserve, mint)), signers),
+            _ if per_mint => return Err(ProgramError::NotEnoughAccountKeys),
+            signers => (None, signers),
+        };
+
         Ok(Self {
             emergency_account,
+            audit_log,
             admin,
+            reserve,
+            signers,
+        })
+    }
+}
+
+/// `[per_mint (u8)][instructions (u16)][reason (u8)?]`, empty data unpausing everything protocol-wide
+pub struct UnpauseInstructionData {
+    /// Whether the unpause targets the mint of the reserve passed after the admin
+    pub per_mint: bool,
+    /// Instruction flags to unpause, `0` unpausing everything
+    pub instructions: u16,
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for UnpauseInstructionData {
+    type Error = ProgramError;
+    
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.is_empty() {
+            return Ok(Self { per_mint: false, instructions: 0, reason: 0 });
+        }
+
+        if data.len() < 3 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let per_mint = match data[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        
+        Ok(Self {
+            per_mint,
+            instructions: u16::from_le_bytes([data[1], data[2]]),
+            reason: data.get(3).copied().unwrap_or(0),
         })
     }
 }
 
 pub struct Unpause<'a> {
     pub accounts: UnpauseAccounts<'a>,
+    pub instruction_data: UnpauseInstructionData,
 }
 
-impl<'a> TryFrom<&'a [AccountInfo]> for Unpause<'a> {
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Unpause<'a> {
     type Error = ProgramError;
     
-    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let accounts = UnpauseAccounts::try_from(accounts)?;
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = UnpauseInstructionData::try_from(data)?;
+        let accounts = UnpauseAccounts::try_from((accounts, instruction_data.per_mint))?;
         
-        Ok(Self { accounts })
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
     }
 }
 
@@ -45,34 +110,65 @@ impl<'a> Unpause<'a> {
     pub const DISCRIMINATOR: &'a u8 = &12;
     
     pub fn process(&self) -> ProgramResult {
-        // Load emergency state
-        let emergency_data = self.accounts.emergency_account.try_borrow_data()?;
-        let emergency_state = unsafe {
-            *(emergency_data.as_ptr() as *const EmergencyState)
-        };
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
         
-        // Verify admin authority
-        if emergency_state.admin != *self.accounts.admin.key() {
-            return Err(ProgramError::InvalidAccountData);
+        // Verify admin authority, guardians can't unpause
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        let instructions = self.instruction_data.instructions;
+
+        // Unpause a single mint
+        if let Some((reserve_account, mint)) = self.accounts.reserve {
+            let mut reserve = load_reserve(reserve_account, self.accounts.emergency_account.key(), mint.key())?;
+            let old_value = reserve.paused_instructions as u64;
+            reserve.paused_instructions &= if instructions == 0 { 0 } else { !instructions };
+            if reserve.paused_instructions == 0 {
+                reserve.pause_expiry = 0;
+            }
+            save_reserve(reserve_account, &reserve)?;
+
+            append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+                *self.accounts.admin.key(), AuditAction::Unpause, *mint.key(), old_value, reserve.paused_instructions as u64,
+                self.instruction_data.reason, Clock::get()?.unix_timestamp,
+            ))?;
+
+            Unpaused { authority: *self.accounts.admin.key(), mint: *mint.key(), instructions }.emit();
+            return Ok(());
         }
         
         // Update state to unpaused
-        drop(emergency_data);
+        let current_timestamp = Clock::get()?.unix_timestamp;
         let mut updated_state = emergency_state;
-        updated_state.is_paused = false;
-        updated_state.last_updated = Clock::get()?.unix_timestamp;
+        updated_state.expire_pause(current_timestamp);
+        if instructions == 0 {
+            updated_state.is_paused = false;
+            updated_state.paused_instructions = 0;
+        } else {
+            updated_state.paused_instructions &= !instructions;
+        }
+        if !updated_state.is_paused && updated_state.paused_instructions == 0 {
+            updated_state.pause_expiry = 0;
+        }
+        updated_state.last_updated = current_timestamp;
+
+        append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+            *self.accounts.admin.key(),
+            AuditAction::Unpause,
+            Pubkey::default(),
+            pause_bits(emergency_state.is_paused, emergency_state.paused_instructions),
+            pause_bits(updated_state.is_paused, updated_state.paused_instructions),
+            self.instruction_data.reason,
+            current_timestamp,
+        ))?;
         
-        // Write updated state
-        let mut emergency_data = self.accounts.emergency_account.try_borrow_mut_data()?;
-        let updated_state_bytes = unsafe {
-            core::slice::from_raw_parts(
-                &updated_state as *const EmergencyState as *const u8,
-                core::mem::size_of::<EmergencyState>(),
-            )
-        };
-        emergency_data[..core::mem::size_of::<EmergencyState>()].copy_from_slice(updated_state_bytes);
+        save_emergency_state(self.accounts.emergency_account, &updated_state)?;
         
+        Unpaused {
+            authority: *self.accounts.admin.key(),
+            mint: Pubkey::default(),
+            instructions,
+        }.emit();
+
         Ok(())
     }
 }
-
@@ -0,0 +1,172 @@
+use pinocchio::{
+    account_info::AccountInfo, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    sysvars::{clock::Clock, Sysvar}, 
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, load_emergency_state, pack_bps, save_emergency_state, verify_admin, AuditAction, AuditEntry, EmergencyState, Event,
+    LimitsUpdated,
+};
+
+pub struct UpdateLimitsAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for UpdateLimitsAccounts<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            signers,
+        })
+    }
+}
+
+pub struct UpdateLimitsInstructionData {
+    pub mode_limit_bps: [u16; 4],
+    pub mode_fee_surcharge_bps: [u16; 4],
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for UpdateLimitsInstructionData {
+    type Error = ProgramError;
+    
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 16 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let mut mode_limit_bps = [0u16; 4];
+        let mut mode_fee_surcharge_bps = [0u16; 4];
+        for i in 0..4 {
+            mode_limit_bps[i] = u16::from_le_bytes([data[i * 2], data[i * 2 + 1]]);
+            mode_fee_surcharge_bps[i] = u16::from_le_bytes([data[8 + i * 2], data[8 + i * 2 + 1]]);
+        }
+
+        // Modes can only degrade the normal limits, never raise them
+        if mode_limit_bps.iter().any(|bps| *bps > 10_000) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        
+        Ok(Self {
+            mode_limit_bps,
+            mode_fee_surcharge_bps,
+            reason: data.get(16).copied().unwrap_or(0),
+        })
+    }
+}
+
+impl UpdateLimitsInstructionData {
+    /// Whether the change raises a mode's limit or lowers its fee surcharge, in which case it
+    /// has to go through the timelock
+    pub fn loosens(&self, emergency_state: &EmergencyState) -> bool {
+        (0..4).any(|i| {
+            self.mode_limit_bps[i] > emergency_state.mode_limit_bps[i]
+                || self.mode_fee_surcharge_bps[i] < emergency_state.mode_fee_surcharge_bps[i]
+        })
+    }
+
+    pub fn apply(&self, emergency_state: &mut EmergencyState) {
+        emergency_state.mode_limit_bps = self.mode_limit_bps;
+        emergency_state.mode_fee_surcharge_bps = self.mode_fee_surcharge_bps;
+    }
+}
+
+/// records the per-mode limits and fee surcharges that changed between `old` and `new` in the audit log,
+/// emitting a LimitsUpdated event
+pub fn log_limits_update(audit_log: &AccountInfo, pool: &Pubkey, actor: &Pubkey, old: &EmergencyState, new: &EmergencyState, reason: u8, now: i64) -> ProgramResult {
+    if old.mode_limit_bps != new.mode_limit_bps {
+        append_audit_entry(audit_log, pool, &AuditEntry::new(
+            *actor, AuditAction::UpdateLimits, Pubkey::default(),
+            pack_bps(old.mode_limit_bps), pack_bps(new.mode_limit_bps), reason, now,
+        ))?;
+    }
+
+    if old.mode_fee_surcharge_bps != new.mode_fee_surcharge_bps {
+        append_audit_entry(audit_log, pool, &AuditEntry::new(
+            *actor, AuditAction::UpdateFeeSurcharges, Pubkey::default(),
+            pack_bps(old.mode_fee_surcharge_bps), pack_bps(new.mode_fee_surcharge_bps), reason, now,
+        ))?;
+    }
+
+    LimitsUpdated {
+        authority: *actor,
+        mode_limit_bps: new.mode_limit_bps,
+        mode_fee_surcharge_bps: new.mode_fee_surcharge_bps,
+    }.emit();
+
+    Ok(())
+}
+
+pub struct UpdateLimits<'a> {
+    pub accounts: UpdateLimitsAccounts<'a>,
+    pub instruction_data: UpdateLimitsInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for UpdateLimits<'a> {
+    type Error = ProgramError;
+    
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = UpdateLimitsAccounts::try_from(accounts)?;
+        let instruction_data = UpdateLimitsInstructionData::try_from(data)?;
+        
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> UpdateLimits<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &14;
+    
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+        
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        // Only tightening applies immediately, loosening is queued with QueueOperation
+        if self.instruction_data.loosens(&emergency_state) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        
+        // Update per-mode limits
+        let mut updated_state = emergency_state;
+        self.instruction_data.apply(&mut updated_state);
+        updated_state.last_updated = Clock::get()?.unix_timestamp;
+
+        log_limits_update(
+            self.accounts.audit_log,
+            self.accounts.emergency_account.key(),
+            self.accounts.admin.key(),
+            &emergency_state,
+            &updated_state,
+            self.instruction_data.reason,
+            updated_state.last_updated,
+        )?;
+        
+        save_emergency_state(self.accounts.emergency_account, &updated_state)?;
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use pinocchio::{
+    account_info::AccountInfo, 
+    program_error::ProgramError, 
+    sysvars::{clock::Clock, Sysvar}, 
+    ProgramResult
+};
+
+use crate::{append_audit_entry, close_account, load_emergency_state, load_timelock_operation, verify_admin, AuditAction, AuditEntry, Event, OperationCancelled};
+
+pub struct CancelOperationAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub operation: &'a AccountInfo,
+    pub payer: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CancelOperationAccounts<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, authority, operation, payer, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            authority,
+            operation,
+            payer,
+            signers,
+        })
+    }
+}
+
+pub struct CancelOperation<'a> {
+    pub accounts: CancelOperationAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CancelOperation<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = CancelOperationAccounts::try_from(accounts)?;
+        
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> CancelOperation<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &32;
+    
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+
+        // Verify admin or guardian authority
+        if verify_admin(&emergency_state, self.accounts.authority, self.accounts.signers).is_err()
+            && !emergency_state.is_guardian(self.accounts.authority.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let operation = load_timelock_operation(self.accounts.operation, self.accounts.emergency_account.key())?;
+        if operation.payer != *self.accounts.payer.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+            *self.accounts.authority.key(),
+            AuditAction::CancelOperation,
+            *self.accounts.operation.key(),
+            operation.action as u64,
+            operation.id,
+            0,
+            Clock::get()?.unix_timestamp,
+        ))?;
+
+        OperationCancelled {
+            authority: *self.accounts.authority.key(),
+            operation: *self.accounts.operation.key(),
+            id: operation.id,
+            action: operation.action as u8,
+        }.emit();
+
+        close_account(self.accounts.operation, self.accounts.payer)
+    }
+}
@@ -0,0 +1,212 @@
+use pinocchio::{
+    account_info::AccountInfo, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    sysvars::{clock::Clock, Sysvar}, 
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, close_account, load_emergency_state, load_keeper_whitelist, load_reserve, load_timelock_operation,
+    log_dead_man_switch_update, log_fee_curve_update, log_fee_tiers_update, log_gate_program_update, log_keeper_update, log_limits_update,
+    log_min_fee_update, log_permissioned_update, log_recovery_update, log_referral_share_update, log_reserve_update, log_treasury_update,
+    save_emergency_state, save_keeper_whitelist, save_reserve, AuditAction, AuditEntry, Event, HeartbeatInstructionData, ModeChanged,
+    OperationExecuted, SetEmergencyModeInstructionData, SetFeeCurveInstructionData, SetFeeTiersInstructionData,
+    SetGateProgramInstructionData, SetKeeperInstructionData, SetMinFeeInstructionData, SetPermissionedInstructionData,
+    SetRecoveryInstructionData, SetReferralShareInstructionData, SetTreasuryInstructionData, TimelockAction, UpdateLimitsInstructionData,
+    UpdateReserveInstructionData,
+};
+
+pub struct ExecuteOperationAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub operation: &'a AccountInfo,
+    pub payer: &'a AccountInfo,
+    /// Account of the changed setting: the target mint's reserve for reserve and fee updates,
+    /// the keeper whitelist for keeper updates, the gate program for gate program updates
+    pub target_account: Option<&'a AccountInfo>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ExecuteOperationAccounts<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        // No signer required, anyone can execute a ready operation
+        let [emergency_account, audit_log, operation, payer, rest @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            operation,
+            payer,
+            target_account: rest.first(),
+        })
+    }
+}
+
+pub struct ExecuteOperation<'a> {
+    pub accounts: ExecuteOperationAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ExecuteOperation<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = ExecuteOperationAccounts::try_from(accounts)?;
+        
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ExecuteOperation<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &31;
+    
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+        // Operations, reserves and keepers are all derived from the pool they were queued in
+        let pool = self.accounts.emergency_account.key();
+        let operation = load_timelock_operation(self.accounts.operation, pool)?;
+
+        if operation.payer != *self.accounts.payer.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        if current_timestamp < operation.eta {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        append_audit_entry(self.accounts.audit_log, pool, &AuditEntry::new(
+            Pubkey::default(),
+            AuditAction::ExecuteOperation,
+            *self.accounts.operation.key(),
+            operation.action as u64,
+            operation.id,
+            0,
+            current_timestamp,
+        ))?;
+
+        // The underlying changes are logged with the operation as actor
+        let actor = self.accounts.operation.key();
+        let mut updated_state = emergency_state;
+        match operation.action {
+            TimelockAction::UpdateLimits => {
+                let instruction_data = UpdateLimitsInstructionData::try_from(operation.payload())?;
+                instruction_data.apply(&mut updated_state);
+                log_limits_update(self.accounts.audit_log, pool, actor, &emergency_state, &updated_state, instruction_data.reason, current_timestamp)?;
+            }
+            TimelockAction::UpdateReserve => {
+                let reserve_account = self.accounts.target_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+                let mut reserve = load_reserve(reserve_account, pool, &operation.target)?;
+                let old_reserve = reserve;
+                let instruction_data = UpdateReserveInstructionData::try_from(operation.payload())?;
+                instruction_data.apply(&mut reserve);
+                save_reserve(reserve_account, &reserve)?;
+                log_reserve_update(self.accounts.audit_log, pool, actor, &old_reserve, &reserve, instruction_data.reason, current_timestamp)?;
+            }
+            TimelockAction::SetFeeTiers => {
+                let reserve_account = self.accounts.target_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+                let mut reserve = load_reserve(reserve_account, pool, &operation.target)?;
+                let old_reserve = reserve;
+                let instruction_data = SetFeeTiersInstructionData::try_from(operation.payload())?;
+                instruction_data.apply(&mut reserve);
+                save_reserve(reserve_account, &reserve)?;
+                log_fee_tiers_update(self.accounts.audit_log, pool, actor, &old_reserve, &reserve, instruction_data.reason, current_timestamp)?;
+            }
+            TimelockAction::SetFeeCurve => {
+                let reserve_account = self.accounts.target_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+                let mut reserve = load_reserve(reserve_account, pool, &operation.target)?;
+                let old_reserve = reserve;
+                let instruction_data = SetFeeCurveInstructionData::try_from(operation.payload())?;
+                instruction_data.apply(&mut reserve);
+                save_reserve(reserve_account, &reserve)?;
+                log_fee_curve_update(self.accounts.audit_log, pool, actor, &old_reserve, &reserve, instruction_data.reason, current_timestamp)?;
+            }
+            TimelockAction::SetMinFee => {
+                let reserve_account = self.accounts.target_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+                let mut reserve = load_reserve(reserve_account, pool, &operation.target)?;
+                let old_reserve = reserve;
+                let instruction_data = SetMinFeeInstructionData::try_from(operation.payload())?;
+                instruction_data.apply(&mut reserve);
+                save_reserve(reserve_account, &reserve)?;
+                log_min_fee_update(self.accounts.audit_log, pool, actor, &old_reserve, &reserve, instruction_data.reason, current_timestamp)?;
+            }
+            TimelockAction::SetKeeper => {
+                let keepers_account = self.accounts.target_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+                let mut keepers = load_keeper_whitelist(keepers_account, pool)?;
+                let old_keeper = keepers.find(&operation.target).copied();
+                let instruction_data = SetKeeperInstructionData::try_from(operation.payload())?;
+                instruction_data.apply(&mut keepers, &operation.target)?;
+                save_keeper_whitelist(keepers_account, &keepers)?;
+                log_keeper_update(
+                    self.accounts.audit_log, pool, actor, &operation.target, old_keeper.as_ref(), keepers.find(&operation.target),
+                    instruction_data.reason, current_timestamp,
+                )?;
+            }
+            TimelockAction::SetPermissioned => {
+                let instruction_data = SetPermissionedInstructionData::try_from(operation.payload())?;
+                instruction_data.apply(&mut updated_state);
+                log_permissioned_update(self.accounts.audit_log, pool, actor, &emergency_state, &updated_state, instruction_data.reason, current_timestamp)?;
+            }
+            TimelockAction::SetGateProgram => {
+                let instruction_data = SetGateProgramInstructionData::try_from(operation.payload())?;
+                if instruction_data.has_gate_program {
+                    let gate_program = self.accounts.target_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+                    if gate_program.key() != &operation.target {
+                        return Err(ProgramError::InvalidAccountData);
+                    }
+                    instruction_data.validate(gate_program)?;
+                }
+                instruction_data.apply(&mut updated_state, &operation.target);
+                log_gate_program_update(self.accounts.audit_log, pool, actor, &emergency_state, &updated_state, instruction_data.reason, current_timestamp)?;
+            }
+            TimelockAction::SetReferralShare => {
+                let instruction_data = SetReferralShareInstructionData::try_from(operation.payload())?;
+                instruction_data.apply(&mut updated_state);
+                log_referral_share_update(self.accounts.audit_log, pool, actor, &emergency_state, &updated_state, instruction_data.reason, current_timestamp)?;
+            }
+            TimelockAction::SetTreasury => {
+                let instruction_data = SetTreasuryInstructionData::try_from(operation.payload())?;
+                instruction_data.apply(&mut updated_state, &operation.target);
+                log_treasury_update(self.accounts.audit_log, pool, actor, &emergency_state, &updated_state, instruction_data.reason, current_timestamp)?;
+            }
+            TimelockAction::SetRecovery => {
+                let instruction_data = SetRecoveryInstructionData::try_from(operation.payload())?;
+                instruction_data.apply(&mut updated_state, &operation.target);
+                log_recovery_update(self.accounts.audit_log, pool, actor, &emergency_state, &updated_state, instruction_data.reason, current_timestamp)?;
+            }
+            TimelockAction::SetDeadManSwitch => {
+                let instruction_data = HeartbeatInstructionData::try_from(operation.payload())?;
+                instruction_data.apply(&mut updated_state);
+                log_dead_man_switch_update(self.accounts.audit_log, pool, actor, &emergency_state, &updated_state, current_timestamp)?;
+            }
+            TimelockAction::SetEmergencyMode => {
+                let instruction_data = SetEmergencyModeInstructionData::try_from(operation.payload())?;
+                updated_state.check_mode_transition(instruction_data.mode, current_timestamp)?;
+                updated_state.set_mode(instruction_data.mode, current_timestamp);
+                append_audit_entry(self.accounts.audit_log, pool, &AuditEntry::new(
+                    *actor, AuditAction::SetEmergencyMode, Pubkey::default(),
+                    emergency_state.emergency_mode as u64, instruction_data.mode as u64, instruction_data.reason, current_timestamp,
+                ))?;
+                ModeChanged {
+                    authority: *actor,
+                    old_mode: emergency_state.emergency_mode as u8,
+                    new_mode: instruction_data.mode as u8,
+                }.emit();
+            }
+        }
+        updated_state.last_updated = current_timestamp;
+        
+        save_emergency_state(self.accounts.emergency_account, &updated_state)?;
+
+        OperationExecuted {
+            operation: *self.accounts.operation.key(),
+            id: operation.id,
+            action: operation.action as u8,
+        }.emit();
+
+        close_account(self.accounts.operation, self.accounts.payer)
+    }
+}
@@ -0,0 +1,7 @@
+pub mod queue;
+pub mod execute;
+pub mod cancel;
+
+pub use queue::*;
+pub use execute::*;
+pub use cancel::*;
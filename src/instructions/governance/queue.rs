@@ -0,0 +1,199 @@
+use pinocchio::{
+    account_info::AccountInfo, 
+    instruction::{Seed, Signer}, 
+    program_error::ProgramError, 
+    pubkey::{self, Pubkey}, 
+    sysvars::{clock::Clock, rent::Rent, Sysvar}, 
+    ProgramResult
+};
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::{
+    append_audit_entry, load_emergency_state, verify_admin, AuditAction, AuditEntry, Event, HeartbeatInstructionData, OperationQueued,
+    SetEmergencyModeInstructionData, SetFeeCurveInstructionData, SetFeeTiersInstructionData, SetGateProgramInstructionData,
+    SetKeeperInstructionData, SetMinFeeInstructionData, SetPermissionedInstructionData, SetRecoveryInstructionData,
+    SetReferralShareInstructionData, SetTreasuryInstructionData, TimelockAction, TimelockOperation, UpdateLimitsInstructionData,
+    UpdateReserveInstructionData, TIMELOCK_DELAY, TIMELOCK_SEED,
+};
+
+pub struct QueueOperationAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    pub payer: &'a AccountInfo,
+    pub operation: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for QueueOperationAccounts<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, payer, operation, _system_program, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() || !payer.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            payer,
+            operation,
+            signers,
+        })
+    }
+}
+
+pub struct QueueOperationInstructionData<'a> {
+    pub id: u64,
+    pub action: TimelockAction,
+    pub target: Pubkey,
+    pub payload: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for QueueOperationInstructionData<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() < 41 || data.len() > 41 + 32 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let id = u64::from_le_bytes(data[0..8].try_into().map_err(|_| ProgramError::InvalidInstructionData)?);
+        let action = TimelockAction::try_from(data[8])?;
+        let target: Pubkey = data[9..41].try_into().map_err(|_| ProgramError::InvalidInstructionData)?;
+        let payload = &data[41..];
+
+        // Rejecting malformed changes now rather than once the delay has passed
+        match action {
+            TimelockAction::UpdateLimits => { UpdateLimitsInstructionData::try_from(payload)?; }
+            TimelockAction::UpdateReserve => { UpdateReserveInstructionData::try_from(payload)?; }
+            TimelockAction::SetEmergencyMode => { SetEmergencyModeInstructionData::try_from(payload)?; }
+            TimelockAction::SetFeeTiers => { SetFeeTiersInstructionData::try_from(payload)?; }
+            TimelockAction::SetFeeCurve => { SetFeeCurveInstructionData::try_from(payload)?; }
+            TimelockAction::SetMinFee => { SetMinFeeInstructionData::try_from(payload)?; }
+            TimelockAction::SetKeeper => { SetKeeperInstructionData::try_from(payload)?; }
+            TimelockAction::SetPermissioned => { SetPermissionedInstructionData::try_from(payload)?; }
+            TimelockAction::SetGateProgram => { SetGateProgramInstructionData::try_from(payload)?; }
+            TimelockAction::SetReferralShare => { SetReferralShareInstructionData::try_from(payload)?; }
+            TimelockAction::SetTreasury => { SetTreasuryInstructionData::try_from(payload)?; }
+            TimelockAction::SetRecovery => { SetRecoveryInstructionData::try_from(payload)?; }
+            TimelockAction::SetDeadManSwitch => {
+                if HeartbeatInstructionData::try_from(payload)?.dead_man_switch.is_none() {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+            }
+        }
+        
+        Ok(Self {
+            id,
+            action,
+            target,
+            payload,
+        })
+    }
+}
+
+pub struct QueueOperation<'a> {
+    pub accounts: QueueOperationAccounts<'a>,
+    pub instruction_data: QueueOperationInstructionData<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for QueueOperation<'a> {
+    type Error = ProgramError;
+    
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = QueueOperationAccounts::try_from(accounts)?;
+        let instruction_data = QueueOperationInstructionData::try_from(data)?;
+        
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> QueueOperation<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &30;
+    
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+        
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        let pool = self.accounts.emergency_account.key();
+        let id = self.instruction_data.id.to_le_bytes();
+        let (operation_key, bump) = pubkey::find_program_address(&[TIMELOCK_SEED, pool, &id], &crate::ID);
+        if operation_key != *self.accounts.operation.key() {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let bump = [bump];
+        let signer_seeds = [
+            Seed::from(TIMELOCK_SEED),
+            Seed::from(pool),
+            Seed::from(&id),
+            Seed::from(&bump),
+        ];
+        let signer_seeds = [Signer::from(&signer_seeds)];
+
+        let space = core::mem::size_of::<TimelockOperation>();
+        CreateAccount {
+            from: self.accounts.payer,
+            to: self.accounts.operation,
+            lamports: Rent::get()?.minimum_balance(space),
+            space: space as u64,
+            owner: &crate::ID,
+        }.invoke_signed(&signer_seeds)?;
+
+        let mut payload = [0u8; 32];
+        payload[..self.instruction_data.payload.len()].copy_from_slice(self.instruction_data.payload);
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let operation = TimelockOperation {
+            id: self.instruction_data.id,
+            eta: current_timestamp + TIMELOCK_DELAY,
+            payer: *self.accounts.payer.key(),
+            target: self.instruction_data.target,
+            action: self.instruction_data.action,
+            bump: bump[0],
+            payload_len: self.instruction_data.payload.len() as u8,
+            payload,
+        };
+
+        let mut operation_data = self.accounts.operation.try_borrow_mut_data()?;
+        let operation_bytes = unsafe {
+            core::slice::from_raw_parts(
+                &operation as *const TimelockOperation as *const u8,
+                core::mem::size_of::<TimelockOperation>(),
+            )
+        };
+        operation_data[..core::mem::size_of::<TimelockOperation>()].copy_from_slice(operation_bytes);
+
+        append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+            *self.accounts.admin.key(),
+            AuditAction::QueueOperation,
+            operation_key,
+            operation.action as u64,
+            operation.id,
+            0,
+            current_timestamp,
+        ))?;
+
+        OperationQueued {
+            authority: *self.accounts.admin.key(),
+            operation: operation_key,
+            id: operation.id,
+            action: operation.action as u8,
+            eta: operation.eta,
+        }.emit();
+
+        Ok(())
+    }
+}
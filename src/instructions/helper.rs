@@ -1,11 +1,19 @@
-use pinocchio::{program_error::ProgramError, ProgramResult};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::{self, Pubkey}, sysvars::{clock::Clock, Sysvar}, ProgramResult};
 
-use crate::{EmergencyMode, EmergencyState};
+use crate::{
+    AuditEntry, AuditLogHeader, BorrowerAllowlist, BorrowerHistory, EmergencyMode, EmergencyState, KeeperWhitelist, MintStats, Referral,
+    Reserve, TimelockOperation, ALLOWLIST_SEED, AUDIT_SEED, BORROWER_SEED, EMERGENCY_SEED, KEEPERS_SEED, PAUSE_LOAN, REFERRAL_SEED,
+    RESERVE_SEED, STATS_SEED, TIMELOCK_SEED,
+};
 
 #[repr(C, packed)]
 pub struct LoanData {
     pub protocol_token_account: [u8; 32],
     pub balance: u64,
+    pub amount: u64,
+    pub fee: u64,
+    /// Referrer credited at repay time, the default key when the loan has none
+    pub referrer: [u8; 32],
 }
 
 pub fn get_token_account(data: &[u8]) -> u64 {
@@ -14,34 +22,417 @@ pub fn get_token_account(data: &[u8]) -> u64 {
     }
 }
 
-/// validation function to check if operation is allowed
-pub fn validate_emergency_state(emergency_state: &EmergencyState, loan_amount: u64) -> ProgramResult {
-    if emergency_state.is_paused {
+pub fn get_token_mint(data: &[u8]) -> Pubkey {
+    unsafe {
+        *(data.as_ptr() as *const Pubkey)
+    }
+}
+
+pub fn get_token_owner(data: &[u8]) -> Pubkey {
+    unsafe {
+        *(data.as_ptr().add(32) as *const Pubkey)
+    }
+}
+
+/// loads a pool's emergency state, checking that the account is the program's emergency PDA for
+/// its pool id, with an expired pause already lifted
+pub fn load_emergency_state(emergency_account: &AccountInfo) -> Result<EmergencyState, ProgramError> {
+    if !emergency_account.is_owned_by(&crate::ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let emergency_data = emergency_account.try_borrow_data()?;
+    if emergency_data.len() < core::mem::size_of::<EmergencyState>() {
         return Err(ProgramError::InvalidAccountData);
     }
-    
-    match emergency_state.emergency_mode {
-        EmergencyMode::Normal => {
-            if loan_amount > emergency_state.max_loan_amount {
-                return Err(ProgramError::InvalidAccountData);
-            }
-        },
-        EmergencyMode::Limited => {
-            let limited_amount = emergency_state.max_loan_amount / 2;
-            if loan_amount > limited_amount {
-                return Err(ProgramError::InvalidAccountData);
-            }
-        },
-        EmergencyMode::Emergency => {
-            let emergency_amount = emergency_state.max_loan_amount / 4;
-            if loan_amount > emergency_amount {
-                return Err(ProgramError::InvalidAccountData);
-            }
-        },
-        EmergencyMode::Frozen => {
+
+    let mut emergency_state = unsafe { *(emergency_data.as_ptr() as *const EmergencyState) };
+
+    let (expected_key, _) = pubkey::find_program_address(&[EMERGENCY_SEED, &emergency_state.pool_id.to_le_bytes()], &crate::ID);
+    if expected_key != *emergency_account.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    emergency_state.expire_pause(Clock::get()?.unix_timestamp);
+
+    Ok(emergency_state)
+}
+
+pub fn save_emergency_state(emergency_account: &AccountInfo, emergency_state: &EmergencyState) -> ProgramResult {
+    let mut emergency_data = emergency_account.try_borrow_mut_data()?;
+    let emergency_state_bytes = unsafe {
+        core::slice::from_raw_parts(
+            emergency_state as *const EmergencyState as *const u8,
+            core::mem::size_of::<EmergencyState>(),
+        )
+    };
+    emergency_data[..core::mem::size_of::<EmergencyState>()].copy_from_slice(emergency_state_bytes);
+
+    Ok(())
+}
+
+/// loads a reserve, checking that the account is the program's reserve PDA for `mint` in `pool`,
+/// with an expired pause already lifted
+pub fn load_reserve(reserve_account: &AccountInfo, pool: &Pubkey, mint: &Pubkey) -> Result<Reserve, ProgramError> {
+    if !reserve_account.is_owned_by(&crate::ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let reserve_data = reserve_account.try_borrow_data()?;
+    if reserve_data.len() != core::mem::size_of::<Reserve>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let mut reserve = unsafe { *(reserve_data.as_ptr() as *const Reserve) };
+
+    if reserve.mint != *mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let expected_key = pubkey::create_program_address(&[RESERVE_SEED, pool, mint, &[reserve.bump]], &crate::ID)?;
+    if expected_key != *reserve_account.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    reserve.expire_pause(Clock::get()?.unix_timestamp);
+
+    Ok(reserve)
+}
+
+pub fn save_reserve(reserve_account: &AccountInfo, reserve: &Reserve) -> ProgramResult {
+    let mut reserve_data = reserve_account.try_borrow_mut_data()?;
+    let reserve_bytes = unsafe {
+        core::slice::from_raw_parts(
+            reserve as *const Reserve as *const u8,
+            core::mem::size_of::<Reserve>(),
+        )
+    };
+    reserve_data[..core::mem::size_of::<Reserve>()].copy_from_slice(reserve_bytes);
+
+    Ok(())
+}
+
+/// loads a mint's statistics, checking that the account is the program's stats PDA for `mint` in `pool`
+pub fn load_mint_stats(stats_account: &AccountInfo, pool: &Pubkey, mint: &Pubkey) -> Result<MintStats, ProgramError> {
+    if !stats_account.is_owned_by(&crate::ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let stats_data = stats_account.try_borrow_data()?;
+    if stats_data.len() != core::mem::size_of::<MintStats>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let stats = unsafe { *(stats_data.as_ptr() as *const MintStats) };
+
+    if stats.mint != *mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let expected_key = pubkey::create_program_address(&[STATS_SEED, pool, mint, &[stats.bump]], &crate::ID)?;
+    if expected_key != *stats_account.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    Ok(stats)
+}
+
+pub fn save_mint_stats(stats_account: &AccountInfo, stats: &MintStats) -> ProgramResult {
+    let mut stats_data = stats_account.try_borrow_mut_data()?;
+    let stats_bytes = unsafe {
+        core::slice::from_raw_parts(
+            stats as *const MintStats as *const u8,
+            core::mem::size_of::<MintStats>(),
+        )
+    };
+    stats_data[..core::mem::size_of::<MintStats>()].copy_from_slice(stats_bytes);
+
+    Ok(())
+}
+
+/// loads a pool's keeper whitelist, checking that the account is the program's keeper whitelist PDA for `pool`
+pub fn load_keeper_whitelist(keepers_account: &AccountInfo, pool: &Pubkey) -> Result<KeeperWhitelist, ProgramError> {
+    if !keepers_account.is_owned_by(&crate::ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let keepers_data = keepers_account.try_borrow_data()?;
+    if keepers_data.len() != core::mem::size_of::<KeeperWhitelist>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let keepers = unsafe { *(keepers_data.as_ptr() as *const KeeperWhitelist) };
+
+    let expected_key = pubkey::create_program_address(&[KEEPERS_SEED, pool, &[keepers.bump]], &crate::ID)?;
+    if expected_key != *keepers_account.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    Ok(keepers)
+}
+
+pub fn save_keeper_whitelist(keepers_account: &AccountInfo, keepers: &KeeperWhitelist) -> ProgramResult {
+    let mut keepers_data = keepers_account.try_borrow_mut_data()?;
+    let keepers_bytes = unsafe {
+        core::slice::from_raw_parts(
+            keepers as *const KeeperWhitelist as *const u8,
+            core::mem::size_of::<KeeperWhitelist>(),
+        )
+    };
+    keepers_data[..core::mem::size_of::<KeeperWhitelist>()].copy_from_slice(keepers_bytes);
+
+    Ok(())
+}
+
+pub fn load_borrower_allowlist(allowlist_account: &AccountInfo, pool: &Pubkey) -> Result<BorrowerAllowlist, ProgramError> {
+    if !allowlist_account.is_owned_by(&crate::ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let allowlist_data = allowlist_account.try_borrow_data()?;
+    if allowlist_data.len() != core::mem::size_of::<BorrowerAllowlist>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let allowlist = unsafe { *(allowlist_data.as_ptr() as *const BorrowerAllowlist) };
+
+    let expected_key = pubkey::create_program_address(&[ALLOWLIST_SEED, pool, &[allowlist.bump]], &crate::ID)?;
+    if expected_key != *allowlist_account.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    Ok(allowlist)
+}
+
+pub fn save_borrower_allowlist(allowlist_account: &AccountInfo, allowlist: &BorrowerAllowlist) -> ProgramResult {
+    let mut allowlist_data = allowlist_account.try_borrow_mut_data()?;
+    let allowlist_bytes = unsafe {
+        core::slice::from_raw_parts(
+            allowlist as *const BorrowerAllowlist as *const u8,
+            core::mem::size_of::<BorrowerAllowlist>(),
+        )
+    };
+    allowlist_data[..core::mem::size_of::<BorrowerAllowlist>()].copy_from_slice(allowlist_bytes);
+
+    Ok(())
+}
+
+/// loads a referrer's referral fees on `mint` in `pool`, checking that the account is the program's referral PDA
+pub fn load_referral(referral_account: &AccountInfo, pool: &Pubkey, referrer: &Pubkey, mint: &Pubkey) -> Result<Referral, ProgramError> {
+    if !referral_account.is_owned_by(&crate::ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let referral_data = referral_account.try_borrow_data()?;
+    if referral_data.len() != core::mem::size_of::<Referral>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let referral = unsafe { *(referral_data.as_ptr() as *const Referral) };
+
+    if referral.referrer != *referrer || referral.mint != *mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let expected_key = pubkey::create_program_address(&[REFERRAL_SEED, pool, referrer, mint, &[referral.bump]], &crate::ID)?;
+    if expected_key != *referral_account.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    Ok(referral)
+}
+
+pub fn save_referral(referral_account: &AccountInfo, referral: &Referral) -> ProgramResult {
+    let mut referral_data = referral_account.try_borrow_mut_data()?;
+    let referral_bytes = unsafe {
+        core::slice::from_raw_parts(
+            referral as *const Referral as *const u8,
+            core::mem::size_of::<Referral>(),
+        )
+    };
+    referral_data[..core::mem::size_of::<Referral>()].copy_from_slice(referral_bytes);
+
+    Ok(())
+}
+
+/// loads a borrower's history on `mint` in `pool`, checking that the account is the program's borrower history PDA
+pub fn load_borrower_history(history_account: &AccountInfo, pool: &Pubkey, borrower: &Pubkey, mint: &Pubkey) -> Result<BorrowerHistory, ProgramError> {
+    if !history_account.is_owned_by(&crate::ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let history_data = history_account.try_borrow_data()?;
+    if history_data.len() != core::mem::size_of::<BorrowerHistory>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let history = unsafe { *(history_data.as_ptr() as *const BorrowerHistory) };
+
+    if history.borrower != *borrower || history.mint != *mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let expected_key = pubkey::create_program_address(&[BORROWER_SEED, pool, borrower, mint, &[history.bump]], &crate::ID)?;
+    if expected_key != *history_account.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    Ok(history)
+}
+
+pub fn save_borrower_history(history_account: &AccountInfo, history: &BorrowerHistory) -> ProgramResult {
+    let mut history_data = history_account.try_borrow_mut_data()?;
+    let history_bytes = unsafe {
+        core::slice::from_raw_parts(
+            history as *const BorrowerHistory as *const u8,
+            core::mem::size_of::<BorrowerHistory>(),
+        )
+    };
+    history_data[..core::mem::size_of::<BorrowerHistory>()].copy_from_slice(history_bytes);
+
+    Ok(())
+}
+
+/// loads a queued operation, checking that the account is the program's operation PDA in `pool`
+pub fn load_timelock_operation(operation_account: &AccountInfo, pool: &Pubkey) -> Result<TimelockOperation, ProgramError> {
+    if !operation_account.is_owned_by(&crate::ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let operation_data = operation_account.try_borrow_data()?;
+    if operation_data.len() != core::mem::size_of::<TimelockOperation>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let operation = unsafe { *(operation_data.as_ptr() as *const TimelockOperation) };
+
+    let expected_key = pubkey::create_program_address(
+        &[TIMELOCK_SEED, pool, &operation.id.to_le_bytes(), &[operation.bump]],
+        &crate::ID,
+    )?;
+    if expected_key != *operation_account.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    Ok(operation)
+}
+
+/// appends an entry to the audit log ring buffer, checking that the account is the audit log PDA of `pool`.
+/// Entries are written in place, the whole log being too large to copy on the stack.
+pub fn append_audit_entry(audit_log: &AccountInfo, pool: &Pubkey, entry: &AuditEntry) -> ProgramResult {
+    if !audit_log.is_owned_by(&crate::ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut audit_data = audit_log.try_borrow_mut_data()?;
+    if audit_data.len() != AuditLogHeader::ACCOUNT_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let header = unsafe { &mut *(audit_data.as_mut_ptr() as *mut AuditLogHeader) };
+
+    let expected_key = pubkey::create_program_address(&[AUDIT_SEED, pool, &[header.bump]], &crate::ID)?;
+    if expected_key != *audit_log.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let offset = AuditLogHeader::entry_offset(header.count);
+    header.count += 1;
+
+    let entry_bytes = unsafe {
+        core::slice::from_raw_parts(
+            entry as *const AuditEntry as *const u8,
+            core::mem::size_of::<AuditEntry>(),
+        )
+    };
+    audit_data[offset..offset + core::mem::size_of::<AuditEntry>()].copy_from_slice(entry_bytes);
+
+    Ok(())
+}
+
+/// closes a program account, refunding its lamports to `destination`
+pub fn close_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    *destination.try_borrow_mut_lamports()? += account.lamports();
+    *account.try_borrow_mut_lamports()? = 0;
+    account.close()
+}
+
+/// checks the admin authority: the admin's signature or, once an admin multisig is configured,
+/// the signatures of at least `admin_threshold` of its signers among `admin` and `signers`
+pub fn verify_admin(emergency_state: &EmergencyState, admin: &AccountInfo, signers: &[AccountInfo]) -> ProgramResult {
+    if emergency_state.admin_threshold == 0 {
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if emergency_state.admin != *admin.key() {
             return Err(ProgramError::InvalidAccountData);
         }
+        return Ok(());
+    }
+
+    let signatures = emergency_state.admin_signers[..emergency_state.admin_signer_count as usize]
+        .iter()
+        .filter(|key| {
+            core::iter::once(admin)
+                .chain(signers)
+                .any(|account| account.is_signer() && account.key() == *key)
+        })
+        .count();
+
+    if signatures < emergency_state.admin_threshold as usize {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    Ok(())
+}
+
+/// validation function to check if operation is allowed, `max_loan_amount` being the limit
+/// of the asset borrowed before the emergency mode is applied
+pub fn validate_emergency_state(emergency_state: &EmergencyState, max_loan_amount: u64, loan_amount: u64) -> ProgramResult {
+    if emergency_state.is_instruction_paused(PAUSE_LOAN) {
+        return Err(ProgramError::InvalidAccountData);
     }
     
+    if emergency_state.emergency_mode == EmergencyMode::Frozen {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if loan_amount > emergency_state.mode_loan_limit(max_loan_amount) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    Ok(())
+}
+
+/// validation function to check if a mint can be borrowed from its reserve
+pub fn validate_reserve(emergency_state: &EmergencyState, reserve: &Reserve, loan_amount: u64) -> ProgramResult {
+    if !reserve.is_enabled || reserve.is_instruction_paused(PAUSE_LOAN) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    validate_emergency_state(emergency_state, reserve.max_loan_amount, loan_amount)?;
+
+    let total_outstanding = reserve.total_outstanding
+        .checked_add(loan_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    if total_outstanding > reserve.max_total_outstanding {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
     Ok(())
 }
+
+/// checks `vault` is the vault InitializeVault created for the reserve's mint, the only one its loans, accrued fees
+/// and referral credits are held in
+pub fn verify_vault(reserve: &Reserve, vault: &AccountInfo) -> ProgramResult {
+    if reserve.vault == Pubkey::default() || reserve.vault != *vault.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
+
+/// circuit breaker: records the loan in the reserve's rolling volume and escalates the emergency
+/// mode one level when it crosses the reserve's threshold. The loan crossing it still goes
+/// through so that the escalation is committed with its transaction, applying to the next loans.
+pub fn track_loan_volume(emergency_state: &mut EmergencyState, reserve: &mut Reserve, loan_amount: u64, now: i64) -> bool {
+    if !reserve.record_volume(loan_amount, now) {
+        return false;
+    }
+
+    emergency_state.escalate(now);
+    emergency_state.last_updated = now;
+    true
+}
@@ -1,32 +1,87 @@
 use std::mem;
 
 use pinocchio::{
-    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, sysvars::{instructions::Instructions, rent::Rent, Sysvar}, ProgramResult
+    account_info::AccountInfo, cpi::slice_invoke, instruction::{AccountMeta, Instruction, Seed, Signer}, program_error::ProgramError, pubkey::{self, Pubkey}, sysvars::{clock::Clock, instructions::{Instructions, INSTRUCTIONS_ID}, rent::Rent, Sysvar}, ProgramResult
 };
 use pinocchio_system::instructions::CreateAccount;
 use pinocchio_token::instructions::Transfer;
 
-use crate::{get_token_account, helper::LoanData};
+use crate::{
+    get_token_account, get_token_mint, helper::LoanData, load_borrower_allowlist, load_borrower_history, load_emergency_state,
+    load_keeper_whitelist, load_mint_stats, load_reserve, save_borrower_history, save_emergency_state, save_mint_stats, save_reserve,
+    track_loan_volume, validate_reserve, verify_vault, BorrowerHistory, Event, FlashLoanError, LoanTaken, ModeChanged, Reserve,
+    BORROWER_SEED, PROTOCOL_SEED,
+};
 
 use crate::repay::Repay;
 
+/// Size of the `[mint][amount]` entry of each borrowed mint in the gate program's instruction data
+pub const GATE_ENTRY_LEN: usize = mem::size_of::<Pubkey>() + mem::size_of::<u64>();
+
 pub struct LoanAccounts<'a> {
     pub borrower: &'a AccountInfo,
     pub protocol: &'a AccountInfo,
     pub loan: &'a AccountInfo,
     pub instruction_sysvar: &'a AccountInfo,
+    pub emergency_account: &'a AccountInfo,
+    pub keepers: &'a AccountInfo,
+    /// Front-end or aggregator credited a share of the fee at repay time, none when this program's id is passed.
+    /// Repay fails unless the admin registered it with RegisterReferrer for every borrowed mint.
+    pub referrer: Option<&'a AccountInfo>,
+    /// Gate program, passed after the borrower allowlist of a permissioned pool when the pool has one
+    pub gate_program: Option<&'a AccountInfo>,
+    /// Accounts forwarded to the gate program, as many as the instruction data's `gate_accounts`
+    pub gate_accounts: &'a [AccountInfo],
+    /// (protocol token account, borrower token account, reserve, stats, borrower history) for each borrowed mint
     pub token_accounts: &'a [AccountInfo],
 }
+
+impl LoanAccounts<'_> {
+    pub const ACCOUNTS_PER_LOAN: usize = 5;
+}
  
-impl<'a> TryFrom<&'a [AccountInfo]> for LoanAccounts<'a> {
+impl<'a> TryFrom<(&'a [AccountInfo], u8)> for LoanAccounts<'a> {
     type Error = ProgramError;
  
-    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [borrower, protocol, loan, instruction_sysvar, _token_program, _system_program, token_accounts @ ..] = accounts else {
+    fn try_from((accounts, gate_account_count): (&'a [AccountInfo], u8)) -> Result<Self, Self::Error> {
+        let [borrower, protocol, loan, instruction_sysvar, _token_program, _system_program, emergency_account, keepers, referrer, token_accounts @ ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
+
+        // Introspected for the Repay instruction and the calling program
+        if instruction_sysvar.key() != &INSTRUCTIONS_ID {
+            return Err(ProgramError::UnsupportedSysvar);
+        }
         
-        if (token_accounts.len() % 2).ne(&0) || token_accounts.len().eq(&0) {
+        let referrer = (referrer.key() != &crate::ID).then_some(referrer);
+
+        // Accounts of the pool's access checks come ahead of the per-mint accounts, their number being
+        // set by the pool and the instruction data rather than guessed from the number of accounts
+        let emergency_state = load_emergency_state(emergency_account)?;
+        if !emergency_state.has_gate_program && gate_account_count != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let pool_account_count = emergency_state.is_permissioned as usize + emergency_state.has_gate_program as usize + gate_account_count as usize;
+        if token_accounts.len() < pool_account_count {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let (mut pool_accounts, token_accounts) = token_accounts.split_at(pool_account_count);
+
+        // Permissioned pools only lend to allowlisted borrowers
+        if emergency_state.is_permissioned {
+            let (allowlist, rest) = pool_accounts.split_first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            if !load_borrower_allowlist(allowlist, emergency_account.key())?.is_allowed(borrower.key()) {
+                return Err(FlashLoanError::BorrowerNotAllowed.into());
+            }
+            pool_accounts = rest;
+        }
+
+        let (gate_program, gate_accounts) = match pool_accounts.split_first() {
+            Some((gate_program, gate_accounts)) => (Some(gate_program), gate_accounts),
+            None => (None, pool_accounts),
+        };
+
+        if token_accounts.len().eq(&0) || token_accounts.len() % Self::ACCOUNTS_PER_LOAN != 0 {
             return Err(ProgramError::InvalidAccountData);
         }
  
@@ -39,14 +94,23 @@ impl<'a> TryFrom<&'a [AccountInfo]> for LoanAccounts<'a> {
             protocol,
             loan,
             instruction_sysvar,
+            emergency_account,
+            keepers,
+            referrer,
+            gate_program,
+            gate_accounts,
             token_accounts,
         })
     }
 }
 
+/// `[bump (u8)][fee (u16)][gate_accounts (u8)][amount (u64)]..`
 pub struct LoanInstructionData<'a> {
     pub bump: [u8; 1],
+    /// Seed of the protocol PDA, the fee charged being set by each reserve
     pub fee: u16,
+    /// Number of accounts forwarded to the gate program, 0 for an ungated pool
+    pub gate_accounts: u8,
     pub amounts: &'a [u64],
 }
  
@@ -60,6 +124,7 @@ impl<'a> TryFrom<&'a [u8]> for LoanInstructionData<'a> {
             return Err(ProgramError::InvalidInstructionData);
         }
         let (fee, data) = data.split_at(mem::size_of::<u16>());
+        let (gate_accounts, data) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
  
         if data.len() % mem::size_of::<u64>() != 0 {
             return Err(ProgramError::InvalidInstructionData);
@@ -75,6 +140,7 @@ impl<'a> TryFrom<&'a [u8]> for LoanInstructionData<'a> {
         Ok(Self { 
             bump: [*bump], 
             fee: u16::from_le_bytes(fee.try_into().map_err(|_| ProgramError::InvalidInstructionData)?), 
+            gate_accounts: *gate_accounts,
             amounts 
         })
     }
@@ -89,10 +155,10 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Loan<'a> {
     type Error = ProgramError;
     
     fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
-        let accounts = LoanAccounts::try_from(accounts)?;
         let instruction_data = LoanInstructionData::try_from(data)?;
+        let accounts = LoanAccounts::try_from((accounts, instruction_data.gate_accounts))?;
  
-        if instruction_data.amounts.len() != accounts.token_accounts.len() / 2 {
+        if instruction_data.amounts.len() != accounts.token_accounts.len() / LoanAccounts::ACCOUNTS_PER_LOAN {
             return Err(ProgramError::InvalidInstructionData);
         }
  
@@ -107,10 +173,31 @@ impl<'a> Loan<'a> {
     pub const DISCRIMINATOR: &'a u8 = &0;
     
     pub fn process(&mut self) -> ProgramResult {
+        let mut emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+        let pool = self.accounts.emergency_account.key();
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let mut escalated = false;
+        let previous_mode = emergency_state.emergency_mode;
+
+        // The gate program can veto the loan by failing
+        if let Some(gate_program) = self.accounts.gate_program {
+            if gate_program.key() != &emergency_state.gate_program {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            self.check_gate(gate_program, self.accounts.gate_accounts)?;
+        }
+
+        // Whitelisted borrowers and programs borrowing through CPI get their own fee and higher limits
+        let keepers = load_keeper_whitelist(self.accounts.keepers, pool)?;
+        let calling_program = self.calling_program()?;
+        let keeper = keepers.find(self.accounts.borrower.key()).or_else(|| keepers.find(&calling_program)).copied();
+
         let fee = self.instruction_data.fee.to_le_bytes();
  
+        // Each pool has its own protocol PDA, so a pool can only lend from its own vaults
         let signer_seeds = [
-            Seed::from("protocol".as_bytes()),
+            Seed::from(PROTOCOL_SEED),
+            Seed::from(pool),
             Seed::from(&fee),
             Seed::from(&self.instruction_data.bump),
         ];
@@ -136,19 +223,45 @@ impl<'a> Loan<'a> {
         };
 
         for (i, amount) in self.instruction_data.amounts.iter().enumerate() {
-            let protocol_token_account = &self.accounts.token_accounts[i * 2];
-            let borrower_token_account = &self.accounts.token_accounts[i * 2 + 1];
+            let protocol_token_account = &self.accounts.token_accounts[i * LoanAccounts::ACCOUNTS_PER_LOAN];
+            let borrower_token_account = &self.accounts.token_accounts[i * LoanAccounts::ACCOUNTS_PER_LOAN + 1];
+            let reserve_account = &self.accounts.token_accounts[i * LoanAccounts::ACCOUNTS_PER_LOAN + 2];
+            let stats_account = &self.accounts.token_accounts[i * LoanAccounts::ACCOUNTS_PER_LOAN + 3];
+            let history_account = &self.accounts.token_accounts[i * LoanAccounts::ACCOUNTS_PER_LOAN + 4];
+
+            // Only registered mints can be borrowed, within their own limits
+            let mint = get_token_mint(&protocol_token_account.try_borrow_data()?);
+            let mut reserve = load_reserve(reserve_account, pool, &mint)?;
+            verify_vault(&reserve, protocol_token_account)?;
+            let max_loan_amount = keeper.map_or(reserve.max_loan_amount, |keeper| keeper.loan_limit(reserve.max_loan_amount));
+            validate_reserve(&emergency_state, &Reserve { max_loan_amount, ..reserve }, *amount)?;
+            escalated |= track_loan_volume(&mut emergency_state, &mut reserve, *amount, current_timestamp);
+
+            reserve.total_outstanding += *amount;
+            save_reserve(reserve_account, &reserve)?;
+
+            let mut stats = load_mint_stats(stats_account, pool, &mint)?;
+            stats.record_loan(*amount);
+            save_mint_stats(stats_account, &stats)?;
+
+            // Fee of the loyalty tier reached before this loan and of the share of the vault borrowed, unless whitelisted
+            let balance = get_token_account(&protocol_token_account.try_borrow_data()?);
+            let mut history = self.load_borrower_history(history_account, &mint)?;
+            let fee_amount = match keeper {
+                Some(keeper) => keeper.quote_fee(&emergency_state, *amount)?,
+                None => reserve.quote_fee(&emergency_state, history.total_borrowed, balance, *amount)?,
+            };
+            history.record_loan(*amount);
+            save_borrower_history(history_account, &history)?;
  
-            let balance = get_token_account(&borrower_token_account.try_borrow_data()?);
-            let balance_with_fee = balance.checked_add(
-                amount.checked_mul(self.instruction_data.fee as u64)
-                    .and_then(|x| x.checked_div(10_000))
-                    .ok_or(ProgramError::InvalidInstructionData)?
-            ).ok_or(ProgramError::InvalidInstructionData)?;
+            let balance_with_fee = balance.checked_add(fee_amount).ok_or(ProgramError::ArithmeticOverflow)?;
  
             loan_entries[i] = LoanData {
                 protocol_token_account: *protocol_token_account.key(),
                 balance: balance_with_fee,
+                amount: *amount,
+                fee: fee_amount,
+                referrer: self.accounts.referrer.map_or(Pubkey::default(), |referrer| *referrer.key()),
             };
  
             Transfer {
@@ -157,8 +270,25 @@ impl<'a> Loan<'a> {
                 authority: self.accounts.protocol,
                 amount: *amount,
             }.invoke_signed(&signer_seeds)?;
+
+            LoanTaken {
+                borrower: *self.accounts.borrower.key(),
+                mint,
+                amount: *amount,
+                fee: fee_amount,
+            }.emit();
         };
 
+        if escalated {
+            save_emergency_state(self.accounts.emergency_account, &emergency_state)?;
+
+            ModeChanged {
+                authority: Pubkey::default(),
+                old_mode: previous_mode as u8,
+                new_mode: emergency_state.emergency_mode as u8,
+            }.emit();
+        }
+
         // Introspecting the Repay instruction
         let instruction_sysvar = unsafe { Instructions::new_unchecked(self.accounts.instruction_sysvar.try_borrow_data()?) };    
 
@@ -174,7 +304,7 @@ impl<'a> Loan<'a> {
             return Err(ProgramError::InvalidInstructionData);
         }
 
-        let instruction = instruction_sysvar.load_instruction_at(num_instructions as usize - 1)?;
+        let instruction = instruction_sysvar.load_instruction_at(num_instructions - 1)?;
  
         if instruction.get_program_id() != &crate::ID {
             return Err(ProgramError::InvalidInstructionData);
@@ -184,9 +314,88 @@ impl<'a> Loan<'a> {
             return Err(ProgramError::InvalidInstructionData);
         }
  
+        // Repay records the fees in its borrower's history and refunds the loan account to them, so it has to be this borrower
+        if unsafe { instruction.get_account_meta_at_unchecked(0).key } != *self.accounts.borrower.key() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+ 
         if unsafe { instruction.get_account_meta_at_unchecked(1).key } != *self.accounts.loan.key() {
             return Err(ProgramError::InvalidInstructionData);
         }
+
+        // The loan has to be repaid to the pool it was taken from
+        if unsafe { instruction.get_account_meta_at_unchecked(2).key } != *pool {
+            return Err(ProgramError::InvalidInstructionData);
+        }
         Ok(())
     }
+
+    /// invokes the gate program with `[borrower, gate accounts..]`, all read-only and unsigned, and one
+    /// `[mint (32)][amount (u64)]` entry per borrowed mint as instruction data
+    fn check_gate(&self, gate_program: &AccountInfo, gate_accounts: &[AccountInfo]) -> ProgramResult {
+        let mut data = Vec::with_capacity(self.instruction_data.amounts.len() * GATE_ENTRY_LEN);
+        for (i, amount) in self.instruction_data.amounts.iter().enumerate() {
+            let protocol_token_account = &self.accounts.token_accounts[i * LoanAccounts::ACCOUNTS_PER_LOAN];
+            data.extend_from_slice(&get_token_mint(&protocol_token_account.try_borrow_data()?));
+            data.extend_from_slice(&amount.to_le_bytes());
+        }
+
+        let account_infos: Vec<&AccountInfo> = core::iter::once(self.accounts.borrower).chain(gate_accounts).collect();
+        let account_metas: Vec<AccountMeta> = account_infos.iter().map(|account| AccountMeta::readonly(account.key())).collect();
+
+        slice_invoke(&Instruction {
+            program_id: gate_program.key(),
+            data: &data,
+            accounts: &account_metas,
+        }, &account_infos)
+    }
+
+    /// program of the transaction instruction invoking Loan, another program than this one when called through CPI
+    fn calling_program(&self) -> Result<Pubkey, ProgramError> {
+        let instruction_sysvar = unsafe { Instructions::new_unchecked(self.accounts.instruction_sysvar.try_borrow_data()?) };
+        let instruction = instruction_sysvar.load_instruction_at(instruction_sysvar.load_current_index() as usize)?;
+        Ok(*instruction.get_program_id())
+    }
+
+    /// loads the borrower's history on `mint` in the pool, creating it at the borrower's expense on their first loan
+    fn load_borrower_history(&self, history_account: &AccountInfo, mint: &Pubkey) -> Result<BorrowerHistory, ProgramError> {
+        let pool = self.accounts.emergency_account.key();
+        let borrower = self.accounts.borrower.key();
+        if history_account.data_len() != 0 {
+            return load_borrower_history(history_account, pool, borrower, mint);
+        }
+
+        let (history_key, bump) = pubkey::find_program_address(&[BORROWER_SEED, pool, borrower, mint], &crate::ID);
+        if history_key != *history_account.key() {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let bump = [bump];
+        let signer_seeds = [
+            Seed::from(BORROWER_SEED),
+            Seed::from(pool),
+            Seed::from(borrower),
+            Seed::from(mint),
+            Seed::from(&bump),
+        ];
+        let signer_seeds = [Signer::from(&signer_seeds)];
+
+        let space = mem::size_of::<BorrowerHistory>();
+        CreateAccount {
+            from: self.accounts.borrower,
+            to: history_account,
+            lamports: Rent::get()?.minimum_balance(space),
+            space: space as u64,
+            owner: &crate::ID,
+        }.invoke_signed(&signer_seeds)?;
+
+        Ok(BorrowerHistory {
+            borrower: *borrower,
+            mint: *mint,
+            total_borrowed: 0,
+            loan_count: 0,
+            total_fees: 0,
+            bump: bump[0],
+        })
+    }
 }
\ No newline at end of file
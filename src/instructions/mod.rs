@@ -2,11 +2,23 @@ pub mod repay;
 pub mod loan;
 pub mod helper;
 pub mod emergency;
+pub mod reserve;
+pub mod governance;
+pub mod access;
+pub mod referral;
+pub mod treasury;
+pub mod recovery;
 
 pub use helper::*;
 pub use loan::*;
 pub use repay::*;
 pub use emergency::*;
+pub use reserve::*;
+pub use governance::*;
+pub use access::*;
+pub use referral::*;
+pub use treasury::*;
+pub use recovery::*;
 
 pub const INITIALIZE_EMERGENCY: u8 = 10;
 pub const PAUSE: u8 = 11;
@@ -14,4 +26,34 @@ pub const UNPAUSE: u8 = 12;
 pub const SET_EMERGENCY_MODE: u8 = 13;
 pub const UPDATE_LIMITS: u8 = 14;
 pub const TRANSFER_ADMIN: u8 = 15;
-pub const ACCEPT_ADMIN: u8 = 16;
\ No newline at end of file
+pub const ACCEPT_ADMIN: u8 = 16;
+pub const SET_GUARDIANS: u8 = 17;
+pub const SET_ADMIN_MULTISIG: u8 = 18;
+pub const PAUSE_WITH_SIGNATURE: u8 = 19;
+
+pub const REGISTER_RESERVE: u8 = 20;
+pub const UPDATE_RESERVE: u8 = 21;
+pub const SET_FEE_TIERS: u8 = 22;
+pub const SET_FEE_CURVE: u8 = 23;
+pub const SET_MIN_FEE: u8 = 24;
+pub const INITIALIZE_VAULT: u8 = 25;
+
+pub const QUEUE_OPERATION: u8 = 30;
+pub const EXECUTE_OPERATION: u8 = 31;
+pub const CANCEL_OPERATION: u8 = 32;
+
+pub const HEARTBEAT: u8 = 40;
+pub const TRIGGER_DEAD_MAN_SWITCH: u8 = 41;
+
+pub const SET_KEEPER: u8 = 50;
+pub const UPDATE_ALLOWLIST: u8 = 51;
+pub const SET_PERMISSIONED: u8 = 52;
+pub const SET_GATE_PROGRAM: u8 = 53;
+
+pub const SET_REFERRAL_SHARE: u8 = 60;
+pub const CLAIM_REFERRAL: u8 = 61;
+
+pub const SET_TREASURY: u8 = 70;
+pub const SWEEP_FEES: u8 = 71;
+pub const SET_RECOVERY: u8 = 80;
+pub const EVACUATE_VAULT: u8 = 81;
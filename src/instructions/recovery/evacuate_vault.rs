@@ -0,0 +1,177 @@
+use std::mem;
+
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult
+};
+use pinocchio_token::instructions::Transfer;
+
+use crate::{
+    append_audit_entry, get_token_account, get_token_mint, get_token_owner, load_emergency_state, load_reserve, save_reserve, verify_admin,
+    verify_vault, AuditAction, AuditEntry, EmergencyMode, Event, FlashLoanError, VaultEvacuated, PROTOCOL_SEED,
+};
+
+pub struct EvacuateVaultAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    pub protocol: &'a AccountInfo,
+    pub reserve: &'a AccountInfo,
+    /// Vault to empty, the one registered for the reserve's mint
+    pub protocol_token_account: &'a AccountInfo,
+    /// Token account of the recovery address for the vault's mint
+    pub recovery_token_account: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for EvacuateVaultAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, protocol, reserve, protocol_token_account, recovery_token_account, _token_program, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            protocol,
+            reserve,
+            protocol_token_account,
+            recovery_token_account,
+            signers,
+        })
+    }
+}
+
+/// `[bump (u8)][fee (u16)][reason (u8)?]`, the seeds of the protocol PDA owning the vault
+pub struct EvacuateVaultInstructionData {
+    pub bump: [u8; 1],
+    pub fee: [u8; 2],
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for EvacuateVaultInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 1 + mem::size_of::<u16>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            bump: [data[0]],
+            fee: [data[1], data[2]],
+            reason: data.get(3).copied().unwrap_or(0),
+        })
+    }
+}
+
+pub struct EvacuateVault<'a> {
+    pub accounts: EvacuateVaultAccounts<'a>,
+    pub instruction_data: EvacuateVaultInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for EvacuateVault<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = EvacuateVaultAccounts::try_from(accounts)?;
+        let instruction_data = EvacuateVaultInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> EvacuateVault<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &81;
+
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        // Last resort against an exploit in progress, pauses don't stop it but anything short of Frozen does
+        if emergency_state.emergency_mode != EmergencyMode::Frozen {
+            return Err(FlashLoanError::NotFrozen.into());
+        }
+
+        if !emergency_state.has_recovery {
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        let pool = self.accounts.emergency_account.key();
+        let (mint, amount) = {
+            let vault_data = self.accounts.protocol_token_account.try_borrow_data()?;
+            (get_token_mint(&vault_data), get_token_account(&vault_data))
+        };
+        let mut reserve = load_reserve(self.accounts.reserve, pool, &mint)?;
+        verify_vault(&reserve, self.accounts.protocol_token_account)?;
+
+        // Funds only go to the pre-registered recovery address's account of the same mint
+        {
+            let recovery_data = self.accounts.recovery_token_account.try_borrow_data()?;
+            if get_token_mint(&recovery_data) != mint || get_token_owner(&recovery_data) != emergency_state.recovery {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        if amount == 0 {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        // The registered vault is the only one fees accrue in, so they all leave with it. Referral credits
+        // stay owed: ClaimReferral only pays them from this vault, once the recovered funds are returned to it.
+        reserve.accrued_fees = 0;
+        save_reserve(self.accounts.reserve, &reserve)?;
+
+        let signer_seeds = [
+            Seed::from(PROTOCOL_SEED),
+            Seed::from(pool),
+            Seed::from(&self.instruction_data.fee),
+            Seed::from(&self.instruction_data.bump),
+        ];
+        let signer_seeds = [Signer::from(&signer_seeds)];
+
+        Transfer {
+            from: self.accounts.protocol_token_account,
+            to: self.accounts.recovery_token_account,
+            authority: self.accounts.protocol,
+            amount,
+        }.invoke_signed(&signer_seeds)?;
+
+        append_audit_entry(self.accounts.audit_log, pool, &AuditEntry::new(
+            *self.accounts.admin.key(),
+            AuditAction::EvacuateVault,
+            mint,
+            amount,
+            0,
+            self.instruction_data.reason,
+            Clock::get()?.unix_timestamp,
+        ))?;
+
+        VaultEvacuated {
+            authority: *self.accounts.admin.key(),
+            mint,
+            vault: *self.accounts.protocol_token_account.key(),
+            recovery_token_account: *self.accounts.recovery_token_account.key(),
+            amount,
+        }.emit();
+
+        Ok(())
+    }
+}
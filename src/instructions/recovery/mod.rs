@@ -0,0 +1,5 @@
+pub mod set_recovery;
+pub mod evacuate_vault;
+
+pub use set_recovery::*;
+pub use evacuate_vault::*;
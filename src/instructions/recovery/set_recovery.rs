@@ -0,0 +1,149 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, load_emergency_state, save_emergency_state, verify_admin, AuditAction, AuditEntry, EmergencyState, Event,
+    RecoveryUpdated,
+};
+
+pub struct SetRecoveryAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    /// Owner of the token accounts vaults are evacuated to
+    pub recovery: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetRecoveryAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, recovery, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            recovery,
+            signers,
+        })
+    }
+}
+
+/// `[has_recovery (u8)][reason (u8)?]`
+pub struct SetRecoveryInstructionData {
+    /// Whether vaults can be evacuated to the recovery address, `false` removing it
+    pub has_recovery: bool,
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for SetRecoveryInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let has_recovery = match data.first() {
+            Some(0) => false,
+            Some(1) => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self {
+            has_recovery,
+            reason: data.get(1).copied().unwrap_or(0),
+        })
+    }
+}
+
+impl SetRecoveryInstructionData {
+    /// Whether the change registers a recovery address or replaces the current one, in which case it
+    /// has to go through the timelock
+    pub fn loosens(&self, state: &EmergencyState, recovery: &Pubkey) -> bool {
+        self.has_recovery && (!state.has_recovery || state.recovery != *recovery)
+    }
+
+    pub fn apply(&self, state: &mut EmergencyState, recovery: &Pubkey) {
+        state.has_recovery = self.has_recovery;
+        state.recovery = if self.has_recovery { *recovery } else { Pubkey::default() };
+    }
+}
+
+/// records a recovery address change in the audit log, emitting a RecoveryUpdated event
+pub fn log_recovery_update(audit_log: &AccountInfo, pool: &Pubkey, actor: &Pubkey, old: &EmergencyState, new: &EmergencyState, reason: u8, now: i64) -> ProgramResult {
+    let target = if new.has_recovery { new.recovery } else { old.recovery };
+    append_audit_entry(audit_log, pool, &AuditEntry::new(
+        *actor, AuditAction::SetRecovery, target, old.has_recovery as u64, new.has_recovery as u64, reason, now,
+    ))?;
+
+    RecoveryUpdated {
+        authority: *actor,
+        recovery: new.recovery,
+        has_recovery: new.has_recovery as u8,
+    }.emit();
+
+    Ok(())
+}
+
+pub struct SetRecovery<'a> {
+    pub accounts: SetRecoveryAccounts<'a>,
+    pub instruction_data: SetRecoveryInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetRecovery<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SetRecoveryAccounts::try_from(accounts)?;
+        let instruction_data = SetRecoveryInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetRecovery<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &80;
+
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        // Removing the recovery address applies immediately, registering or replacing it is queued with
+        // QueueOperation so that a compromised admin can't redirect an evacuation
+        let recovery = self.accounts.recovery.key();
+        if self.instruction_data.loosens(&emergency_state, recovery) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let mut updated_state = emergency_state;
+        self.instruction_data.apply(&mut updated_state, recovery);
+        updated_state.last_updated = Clock::get()?.unix_timestamp;
+        save_emergency_state(self.accounts.emergency_account, &updated_state)?;
+
+        log_recovery_update(
+            self.accounts.audit_log,
+            self.accounts.emergency_account.key(),
+            self.accounts.admin.key(),
+            &emergency_state,
+            &updated_state,
+            self.instruction_data.reason,
+            updated_state.last_updated,
+        )
+    }
+}
@@ -0,0 +1,139 @@
+use std::mem;
+
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    ProgramResult
+};
+use pinocchio_token::instructions::Transfer;
+
+use crate::{
+    get_token_mint, load_emergency_state, load_referral, load_reserve, save_referral, verify_vault, EmergencyMode, Event, ReferralClaimed,
+    PAUSE_WITHDRAW, PROTOCOL_SEED,
+};
+
+pub struct ClaimReferralAccounts<'a> {
+    pub referrer: &'a AccountInfo,
+    pub referral: &'a AccountInfo,
+    pub protocol: &'a AccountInfo,
+    /// Vault of the referral's mint the fees are paid from, the one registered for the mint
+    pub protocol_token_account: &'a AccountInfo,
+    pub referrer_token_account: &'a AccountInfo,
+    pub emergency_account: &'a AccountInfo,
+    pub reserve: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ClaimReferralAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [referrer, referral, protocol, protocol_token_account, referrer_token_account, _token_program, emergency_account, reserve, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !referrer.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            referrer,
+            referral,
+            protocol,
+            protocol_token_account,
+            referrer_token_account,
+            emergency_account,
+            reserve,
+        })
+    }
+}
+
+/// `[bump (u8)][fee (u16)]`, the seeds of the protocol PDA owning the vault
+pub struct ClaimReferralInstructionData {
+    pub bump: [u8; 1],
+    pub fee: [u8; 2],
+}
+
+impl TryFrom<&[u8]> for ClaimReferralInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 1 + mem::size_of::<u16>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            bump: [data[0]],
+            fee: [data[1], data[2]],
+        })
+    }
+}
+
+pub struct ClaimReferral<'a> {
+    pub accounts: ClaimReferralAccounts<'a>,
+    pub instruction_data: ClaimReferralInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for ClaimReferral<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = ClaimReferralAccounts::try_from(accounts)?;
+        let instruction_data = ClaimReferralInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> ClaimReferral<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &61;
+
+    pub fn process(&self) -> ProgramResult {
+        // Claims move funds out of the vaults, so they stop with withdrawals
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+        if emergency_state.is_instruction_paused(PAUSE_WITHDRAW) || emergency_state.emergency_mode == EmergencyMode::Frozen {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let pool = self.accounts.emergency_account.key();
+        let mint = get_token_mint(&self.accounts.protocol_token_account.try_borrow_data()?);
+        let mut referral = load_referral(self.accounts.referral, pool, self.accounts.referrer.key(), &mint)?;
+
+        // Referral shares are credited on fees repaid to the mint's registered vault, and only paid from it
+        let reserve = load_reserve(self.accounts.reserve, pool, &mint)?;
+        verify_vault(&reserve, self.accounts.protocol_token_account)?;
+
+        let amount = referral.unclaimed;
+        if amount == 0 {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        referral.unclaimed = 0;
+        save_referral(self.accounts.referral, &referral)?;
+
+        let signer_seeds = [
+            Seed::from(PROTOCOL_SEED),
+            Seed::from(pool),
+            Seed::from(&self.instruction_data.fee),
+            Seed::from(&self.instruction_data.bump),
+        ];
+        let signer_seeds = [Signer::from(&signer_seeds)];
+
+        Transfer {
+            from: self.accounts.protocol_token_account,
+            to: self.accounts.referrer_token_account,
+            authority: self.accounts.protocol,
+            amount,
+        }.invoke_signed(&signer_seeds)?;
+
+        ReferralClaimed {
+            referrer: *self.accounts.referrer.key(),
+            mint,
+            amount,
+        }.emit();
+
+        Ok(())
+    }
+}
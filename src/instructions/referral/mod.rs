@@ -0,0 +1,7 @@
+pub mod set_referral_share;
+pub mod register_referrer;
+pub mod claim_referral;
+
+pub use set_referral_share::*;
+pub use register_referrer::*;
+pub use claim_referral::*;
@@ -0,0 +1,156 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult
+};
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::{
+    append_audit_entry, load_emergency_state, save_referral, verify_admin, AuditAction, AuditEntry, Event, Referral, ReferrerRegistered,
+    REFERRAL_SEED,
+};
+
+pub struct RegisterReferrerAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    /// Admin, paying for the referral PDA
+    pub admin: &'a AccountInfo,
+    /// Referral PDA to create
+    pub referral: &'a AccountInfo,
+    /// Front-end or aggregator to register
+    pub referrer: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for RegisterReferrerAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, referral, referrer, mint, _system_program, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            referral,
+            referrer,
+            mint,
+            signers,
+        })
+    }
+}
+
+/// `[reason (u8)?]`
+pub struct RegisterReferrerInstructionData {
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for RegisterReferrerInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self {
+            reason: data.first().copied().unwrap_or(0),
+        })
+    }
+}
+
+pub struct RegisterReferrer<'a> {
+    pub accounts: RegisterReferrerAccounts<'a>,
+    pub instruction_data: RegisterReferrerInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for RegisterReferrer<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = RegisterReferrerAccounts::try_from(accounts)?;
+        let instruction_data = RegisterReferrerInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> RegisterReferrer<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &63;
+
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        // Loans only name referrers whose referral PDA the admin created, so a borrower can't refer itself
+        if self.accounts.referral.data_len() != 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let pool = self.accounts.emergency_account.key();
+        let referrer = self.accounts.referrer.key();
+        let mint = self.accounts.mint.key();
+        let (referral_key, bump) = pubkey::find_program_address(&[REFERRAL_SEED, pool, referrer, mint], &crate::ID);
+        if referral_key != *self.accounts.referral.key() {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let bump = [bump];
+        let signer_seeds = [
+            Seed::from(REFERRAL_SEED),
+            Seed::from(pool),
+            Seed::from(referrer),
+            Seed::from(mint),
+            Seed::from(&bump),
+        ];
+        let signer_seeds = [Signer::from(&signer_seeds)];
+
+        let space = core::mem::size_of::<Referral>();
+        CreateAccount {
+            from: self.accounts.admin,
+            to: self.accounts.referral,
+            lamports: Rent::get()?.minimum_balance(space),
+            space: space as u64,
+            owner: &crate::ID,
+        }.invoke_signed(&signer_seeds)?;
+
+        save_referral(self.accounts.referral, &Referral {
+            referrer: *referrer,
+            mint: *mint,
+            unclaimed: 0,
+            total_earned: 0,
+            bump: bump[0],
+        })?;
+
+        append_audit_entry(self.accounts.audit_log, pool, &AuditEntry::new(
+            *self.accounts.admin.key(),
+            AuditAction::RegisterReferrer,
+            referral_key,
+            0,
+            0,
+            self.instruction_data.reason,
+            Clock::get()?.unix_timestamp,
+        ))?;
+
+        ReferrerRegistered {
+            authority: *self.accounts.admin.key(),
+            referrer: *referrer,
+            mint: *mint,
+        }.emit();
+
+        Ok(())
+    }
+}
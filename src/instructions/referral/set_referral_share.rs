@@ -0,0 +1,144 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, load_emergency_state, save_emergency_state, verify_admin, AuditAction, AuditEntry, EmergencyState, Event,
+    ReferralShareUpdated,
+};
+
+pub struct SetReferralShareAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetReferralShareAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            signers,
+        })
+    }
+}
+
+/// `[referral_share_bps (u16)][reason (u8)?]`
+pub struct SetReferralShareInstructionData {
+    /// Share of a referred loan's fee credited to its referrer, at most 10_000 (basis points)
+    pub referral_share_bps: u16,
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for SetReferralShareInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 2 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let referral_share_bps = u16::from_le_bytes([data[0], data[1]]);
+        if referral_share_bps > 10_000 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            referral_share_bps,
+            reason: data.get(2).copied().unwrap_or(0),
+        })
+    }
+}
+
+impl SetReferralShareInstructionData {
+    /// Whether the change raises the share given away to referrers, in which case it has to go through the timelock
+    pub fn loosens(&self, state: &EmergencyState) -> bool {
+        self.referral_share_bps > state.referral_share_bps
+    }
+
+    pub fn apply(&self, state: &mut EmergencyState) {
+        state.referral_share_bps = self.referral_share_bps;
+    }
+}
+
+/// records a referral share change in the audit log, emitting a ReferralShareUpdated event
+pub fn log_referral_share_update(audit_log: &AccountInfo, pool: &Pubkey, actor: &Pubkey, old: &EmergencyState, new: &EmergencyState, reason: u8, now: i64) -> ProgramResult {
+    append_audit_entry(audit_log, pool, &AuditEntry::new(
+        *actor, AuditAction::SetReferralShare, Pubkey::default(),
+        old.referral_share_bps as u64, new.referral_share_bps as u64, reason, now,
+    ))?;
+
+    ReferralShareUpdated {
+        authority: *actor,
+        referral_share_bps: new.referral_share_bps,
+    }.emit();
+
+    Ok(())
+}
+
+pub struct SetReferralShare<'a> {
+    pub accounts: SetReferralShareAccounts<'a>,
+    pub instruction_data: SetReferralShareInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetReferralShare<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SetReferralShareAccounts::try_from(accounts)?;
+        let instruction_data = SetReferralShareInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetReferralShare<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &60;
+
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        // Lowering the share applies immediately, raising it is queued with QueueOperation
+        if self.instruction_data.loosens(&emergency_state) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let mut updated_state = emergency_state;
+        self.instruction_data.apply(&mut updated_state);
+        updated_state.last_updated = Clock::get()?.unix_timestamp;
+        save_emergency_state(self.accounts.emergency_account, &updated_state)?;
+
+        log_referral_share_update(
+            self.accounts.audit_log,
+            self.accounts.emergency_account.key(),
+            self.accounts.admin.key(),
+            &emergency_state,
+            &updated_state,
+            self.instruction_data.reason,
+            updated_state.last_updated,
+        )
+    }
+}
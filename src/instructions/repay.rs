@@ -1,26 +1,38 @@
 use std::mem;
 
-use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult};
 
-use crate::{get_token_account, LoanData};
+use crate::{
+    get_token_account, get_token_mint, load_borrower_history, load_emergency_state, load_mint_stats, load_referral, load_reserve,
+    save_borrower_history, save_mint_stats, save_referral, save_reserve, Event, LoanData, LoanRepaid, ReferralCredited, PAUSE_REPAY,
+};
 
 pub struct RepayAccounts<'a> {
     pub borrower: &'a AccountInfo,
     pub loan: &'a AccountInfo,
+    pub emergency_account: &'a AccountInfo,
+    /// (protocol token account, reserve, stats, borrower history) for each loan entry, followed by the
+    /// referrer's referral PDA when the loan has a referrer
     pub token_accounts: &'a [AccountInfo],
 }
+
+impl RepayAccounts<'_> {
+    pub const ACCOUNTS_PER_LOAN: usize = 4;
+    pub const ACCOUNTS_PER_REFERRED_LOAN: usize = 5;
+}
  
 impl<'a> TryFrom<&'a [AccountInfo]> for RepayAccounts<'a> {
     type Error = ProgramError;
  
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [borrower, loan, token_accounts @ ..] = accounts else {
+        let [borrower, loan, emergency_account, token_accounts @ ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
  
         Ok(Self {
             borrower,
             loan,
+            emergency_account,
             token_accounts,
         })
     }
@@ -44,15 +56,37 @@ impl<'a> TryFrom<&'a [AccountInfo]> for Repay<'a> {
     pub const DISCRIMINATOR: &'a u8 = &1;
 
     pub fn process(&mut self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+        if emergency_state.is_instruction_paused(PAUSE_REPAY) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let pool = self.accounts.emergency_account.key();
+
         let loan_data = self.accounts.loan.try_borrow_data()?;
         let loan_num = loan_data.len() / size_of::<LoanData>();
 
-        if loan_num.ne(&self.accounts.token_accounts.len()) {
+        // The referrer is the same for every entry of the loan
+        let referrer: Pubkey = if loan_num > 0 {
+            unsafe { *(loan_data.as_ptr().add(size_of::<[u8; 32]>() + 3 * size_of::<u64>()) as *const [u8; 32]) }
+        } else {
+            Pubkey::default()
+        };
+        let is_referred = referrer != Pubkey::default();
+        let accounts_per_loan = if is_referred {
+            RepayAccounts::ACCOUNTS_PER_REFERRED_LOAN
+        } else {
+            RepayAccounts::ACCOUNTS_PER_LOAN
+        };
+
+        if (loan_num * accounts_per_loan).ne(&self.accounts.token_accounts.len()) {
             return Err(ProgramError::InvalidAccountData);
         }
 
         for i in 0..loan_num {
-            let protocol_token_account = &self.accounts.token_accounts[i];
+            let protocol_token_account = &self.accounts.token_accounts[i * accounts_per_loan];
+            let reserve_account = &self.accounts.token_accounts[i * accounts_per_loan + 1];
+            let stats_account = &self.accounts.token_accounts[i * accounts_per_loan + 2];
+            let history_account = &self.accounts.token_accounts[i * accounts_per_loan + 3];
             let expected_key = unsafe { *(loan_data.as_ptr().add(i * mem::size_of::<LoanData>()) as *const [u8; 32]) };
             let actual_key = *protocol_token_account.key();
             if expected_key != actual_key {
@@ -68,6 +102,58 @@ impl<'a> TryFrom<&'a [AccountInfo]> for Repay<'a> {
             if balance < loan_balance {
                 return Err(ProgramError::InvalidAccountData);
             }
+
+            // Release the borrowed amount from the reserve's outstanding total
+            let loan_amount = unsafe {
+                *(loan_data.as_ptr().add(i * mem::size_of::<LoanData>() + size_of::<[u8; 32]>() + size_of::<u64>()) as *const u64)
+            };
+            let fee = unsafe {
+                *(loan_data.as_ptr().add(i * mem::size_of::<LoanData>() + size_of::<[u8; 32]>() + 2 * size_of::<u64>()) as *const u64)
+            };
+            let mint = get_token_mint(&protocol_token_account.try_borrow_data()?);
+            let mut reserve = load_reserve(reserve_account, pool, &mint)?;
+            if reserve.is_instruction_paused(PAUSE_REPAY) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            reserve.total_outstanding = reserve.total_outstanding.saturating_sub(loan_amount);
+
+            let mut stats = load_mint_stats(stats_account, pool, &mint)?;
+            stats.record_repay(fee);
+            save_mint_stats(stats_account, &stats)?;
+            let mut history = load_borrower_history(history_account, pool, self.accounts.borrower.key(), &mint)?;
+            history.record_repay(fee);
+            save_borrower_history(history_account, &history)?;
+
+            // The referrer's share stays in the vault until claimed, only referrers registered by the admin
+            // for the mint having a referral PDA to credit
+            let referral_share = if is_referred {
+                let referral_account = &self.accounts.token_accounts[i * accounts_per_loan + 4];
+                let mut referral = load_referral(referral_account, pool, &referrer, &mint)?;
+                let share = emergency_state.referral_share(fee);
+                referral.credit(share);
+                save_referral(referral_account, &referral)?;
+
+                ReferralCredited {
+                    referrer,
+                    borrower: *self.accounts.borrower.key(),
+                    mint,
+                    amount: share,
+                }.emit();
+                share
+            } else {
+                0
+            };
+
+            // The rest of the fee accrues to the treasury until swept
+            reserve.accrued_fees = reserve.accrued_fees.saturating_add(fee - referral_share);
+            save_reserve(reserve_account, &reserve)?;
+
+            LoanRepaid {
+                borrower: *self.accounts.borrower.key(),
+                mint,
+                amount: loan_amount,
+                fee,
+            }.emit();
         }
 
         drop(loan_data);
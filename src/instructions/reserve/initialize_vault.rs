@@ -0,0 +1,191 @@
+use std::mem;
+
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::{self, Pubkey},
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult
+};
+use pinocchio_system::instructions::CreateAccount;
+use pinocchio_token::{instructions::InitializeAccount3, state::TokenAccount};
+
+use crate::{
+    load_emergency_state, load_reserve, save_reserve, verify_admin, Event, RegisterReserve, RegisterReserveAccounts,
+    RegisterReserveInstructionData, VaultInitialized, PROTOCOL_SEED, VAULT_SEED,
+};
+
+pub struct InitializeVaultAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    pub payer: &'a AccountInfo,
+    pub reserve: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    /// Vault token account PDA to create
+    pub vault: &'a AccountInfo,
+    /// Protocol PDA owning the vault
+    pub protocol: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeVaultAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, payer, reserve, mint, stats, _system_program, vault, protocol, _token_program, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            payer,
+            reserve,
+            mint,
+            stats,
+            vault,
+            protocol,
+            signers,
+        })
+    }
+}
+
+/// `[fee (u16)]`, the seed of the pool's protocol PDA, followed by the RegisterReserve data when the mint
+/// isn't registered yet
+pub struct InitializeVaultInstructionData {
+    pub fee: [u8; 2],
+    /// Registration of the mint, `None` to create the vault of an already registered mint
+    pub reserve: Option<RegisterReserveInstructionData>,
+}
+
+impl TryFrom<&[u8]> for InitializeVaultInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < mem::size_of::<u16>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let reserve = match &data[2..] {
+            [] => None,
+            reserve => Some(RegisterReserveInstructionData::try_from(reserve)?),
+        };
+
+        Ok(Self {
+            fee: [data[0], data[1]],
+            reserve,
+        })
+    }
+}
+
+pub struct InitializeVault<'a> {
+    pub accounts: InitializeVaultAccounts<'a>,
+    pub instruction_data: InitializeVaultInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for InitializeVault<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = InitializeVaultAccounts::try_from(accounts)?;
+        let instruction_data = InitializeVaultInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> InitializeVault<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &25;
+
+    pub fn process(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+        let pool = accounts.emergency_account.key();
+
+        if accounts.reserve.data_len() == 0 {
+            // Registering the mint verifies the admin authority
+            let reserve = self.instruction_data.reserve.ok_or(ProgramError::InvalidInstructionData)?;
+            RegisterReserve {
+                accounts: RegisterReserveAccounts {
+                    emergency_account: accounts.emergency_account,
+                    audit_log: accounts.audit_log,
+                    admin: accounts.admin,
+                    payer: accounts.payer,
+                    reserve: accounts.reserve,
+                    mint: accounts.mint,
+                    stats: accounts.stats,
+                    signers: accounts.signers,
+                },
+                instruction_data: reserve,
+            }.process()?;
+        } else {
+            // Mint registered before vaults could be created, only its vault is missing
+            let emergency_state = load_emergency_state(accounts.emergency_account)?;
+            verify_admin(&emergency_state, accounts.admin, accounts.signers)?;
+        }
+
+        // A mint has a single vault, its loans, fees and referral credits all being held in it
+        let mut reserve = load_reserve(accounts.reserve, pool, accounts.mint.key())?;
+        if reserve.vault != Pubkey::default() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        // The vault is only usable by Loan if the protocol PDA owns it
+        let (protocol, _) = pubkey::find_program_address(&[PROTOCOL_SEED, pool, &self.instruction_data.fee], &crate::ID);
+        if protocol != *accounts.protocol.key() {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let mint = accounts.mint.key();
+        let (vault_key, vault_bump) = pubkey::find_program_address(&[VAULT_SEED, &protocol, mint], &crate::ID);
+        if vault_key != *accounts.vault.key() {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let vault_bump = [vault_bump];
+        let signer_seeds = [
+            Seed::from(VAULT_SEED),
+            Seed::from(&protocol),
+            Seed::from(mint),
+            Seed::from(&vault_bump),
+        ];
+        let signer_seeds = [Signer::from(&signer_seeds)];
+
+        CreateAccount {
+            from: accounts.payer,
+            to: accounts.vault,
+            lamports: Rent::get()?.minimum_balance(TokenAccount::LEN),
+            space: TokenAccount::LEN as u64,
+            owner: &pinocchio_token::ID,
+        }.invoke_signed(&signer_seeds)?;
+
+        InitializeAccount3 {
+            account: accounts.vault,
+            mint: accounts.mint,
+            owner: &protocol,
+        }.invoke()?;
+
+        reserve.vault = vault_key;
+        save_reserve(accounts.reserve, &reserve)?;
+
+        VaultInitialized {
+            authority: *accounts.admin.key(),
+            mint: *mint,
+            vault: vault_key,
+            protocol,
+        }.emit();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,13 @@
+pub mod register;
+pub mod update;
+pub mod set_fee_tiers;
+pub mod set_fee_curve;
+pub mod set_min_fee;
+pub mod initialize_vault;
+
+pub use register::*;
+pub use update::*;
+pub use set_fee_tiers::*;
+pub use set_fee_curve::*;
+pub use set_min_fee::*;
+pub use initialize_vault::*;
@@ -0,0 +1,232 @@
+use pinocchio::{
+    account_info::AccountInfo, 
+    instruction::{Seed, Signer},
+    program_error::ProgramError, 
+    pubkey::{self, Pubkey}, 
+    sysvars::{clock::Clock, rent::Rent, Sysvar}, 
+    ProgramResult
+};
+use pinocchio_system::instructions::CreateAccount;
+use pinocchio_token::state::Mint;
+
+use crate::{append_audit_entry, load_emergency_state, save_mint_stats, save_reserve, verify_admin, AuditAction, AuditEntry, Event, MintStats, Reserve, ReserveUpdated, MAX_FEE_CURVE_POINTS, MAX_FEE_TIERS, RESERVE_SEED, STATS_SEED};
+
+pub struct RegisterReserveAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    pub payer: &'a AccountInfo,
+    pub reserve: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for RegisterReserveAccounts<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, payer, reserve, mint, stats, _system_program, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            payer,
+            reserve,
+            mint,
+            stats,
+            signers,
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct RegisterReserveInstructionData {
+    pub max_loan_amount: u64,
+    pub max_total_outstanding: u64,
+    /// Circuit breaker threshold, `0` (or omitted) leaving it disabled
+    pub volume_threshold: u64,
+    /// Base fee, `Reserve::DEFAULT_FEE_BPS` when omitted
+    pub fee_bps: u16,
+    /// Minimum fee of a loan, `0` when omitted
+    pub min_fee: u64,
+}
+
+impl TryFrom<&[u8]> for RegisterReserveInstructionData {
+    type Error = ProgramError;
+    
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 16 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        
+        let max_loan_amount = u64::from_le_bytes(
+            data[0..8].try_into().map_err(|_| ProgramError::InvalidInstructionData)?
+        );
+        let max_total_outstanding = u64::from_le_bytes(
+            data[8..16].try_into().map_err(|_| ProgramError::InvalidInstructionData)?
+        );
+        let volume_threshold = match data.get(16..24) {
+            Some(threshold) => u64::from_le_bytes(threshold.try_into().map_err(|_| ProgramError::InvalidInstructionData)?),
+            None => 0,
+        };
+        let fee_bps = match data.get(24..26) {
+            Some(fee_bps) => u16::from_le_bytes([fee_bps[0], fee_bps[1]]),
+            None => Reserve::DEFAULT_FEE_BPS,
+        };
+        if fee_bps > 10_000 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let min_fee = match data.get(26..34) {
+            Some(min_fee) => u64::from_le_bytes(min_fee.try_into().map_err(|_| ProgramError::InvalidInstructionData)?),
+            None => 0,
+        };
+        
+        Ok(Self {
+            max_loan_amount,
+            max_total_outstanding,
+            volume_threshold,
+            fee_bps,
+            min_fee,
+        })
+    }
+}
+
+pub struct RegisterReserve<'a> {
+    pub accounts: RegisterReserveAccounts<'a>,
+    pub instruction_data: RegisterReserveInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for RegisterReserve<'a> {
+    type Error = ProgramError;
+    
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = RegisterReserveAccounts::try_from(accounts)?;
+        let instruction_data = RegisterReserveInstructionData::try_from(data)?;
+        
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> RegisterReserve<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &20;
+    
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+        
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        let decimals = Mint::from_account_info(self.accounts.mint)?.decimals();
+
+        let pool = self.accounts.emergency_account.key();
+        let mint = self.accounts.mint.key();
+        let (reserve_key, bump) = pubkey::find_program_address(&[RESERVE_SEED, pool, mint], &crate::ID);
+        if reserve_key != *self.accounts.reserve.key() {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let bump = [bump];
+        let signer_seeds = [
+            Seed::from(RESERVE_SEED),
+            Seed::from(pool),
+            Seed::from(mint),
+            Seed::from(&bump),
+        ];
+        let signer_seeds = [Signer::from(&signer_seeds)];
+
+        let space = core::mem::size_of::<Reserve>();
+        CreateAccount {
+            from: self.accounts.payer,
+            to: self.accounts.reserve,
+            lamports: Rent::get()?.minimum_balance(space),
+            space: space as u64,
+            owner: &crate::ID,
+        }.invoke_signed(&signer_seeds)?;
+
+        let reserve = Reserve {
+            mint: *mint,
+            max_loan_amount: self.instruction_data.max_loan_amount,
+            max_total_outstanding: self.instruction_data.max_total_outstanding,
+            total_outstanding: 0,
+            is_enabled: true,
+            decimals,
+            bump: bump[0],
+            paused_instructions: 0,
+            volume_threshold: self.instruction_data.volume_threshold,
+            volume_window_start: 0,
+            volume_current: 0,
+            volume_previous: 0,
+            fee_tier_volumes: [0; MAX_FEE_TIERS],
+            fee_bps: self.instruction_data.fee_bps,
+            fee_tier_bps: [0; MAX_FEE_TIERS],
+            fee_curve_utilization_bps: [0; MAX_FEE_CURVE_POINTS],
+            fee_curve_bps: [0; MAX_FEE_CURVE_POINTS],
+            min_fee: self.instruction_data.min_fee,
+            accrued_fees: 0,
+            pause_expiry: 0,
+            // Set once InitializeVault creates the mint's vault, the mint can't be lent until then
+            vault: Pubkey::default(),
+        };
+
+        save_reserve(self.accounts.reserve, &reserve)?;
+
+        // Statistics updated by Loan and Repay
+        let (stats_key, stats_bump) = pubkey::find_program_address(&[STATS_SEED, pool, mint], &crate::ID);
+        if stats_key != *self.accounts.stats.key() {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let stats_bump = [stats_bump];
+        let signer_seeds = [
+            Seed::from(STATS_SEED),
+            Seed::from(pool),
+            Seed::from(mint),
+            Seed::from(&stats_bump),
+        ];
+        let signer_seeds = [Signer::from(&signer_seeds)];
+
+        let space = core::mem::size_of::<MintStats>();
+        CreateAccount {
+            from: self.accounts.payer,
+            to: self.accounts.stats,
+            lamports: Rent::get()?.minimum_balance(space),
+            space: space as u64,
+            owner: &crate::ID,
+        }.invoke_signed(&signer_seeds)?;
+
+        save_mint_stats(self.accounts.stats, &MintStats {
+            mint: *mint,
+            total_borrowed: 0,
+            total_fees: 0,
+            loan_count: 0,
+            largest_loan: 0,
+            bump: stats_bump[0],
+        })?;
+
+        append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+            *self.accounts.admin.key(),
+            AuditAction::RegisterReserve,
+            *mint,
+            0,
+            reserve.max_loan_amount,
+            0,
+            Clock::get()?.unix_timestamp,
+        ))?;
+
+        ReserveUpdated::from_reserve(*self.accounts.admin.key(), &reserve).emit();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, load_emergency_state, load_reserve, pack_bps, save_reserve, verify_admin, AuditAction,
+    AuditEntry, Event, FeeCurveUpdated, Reserve, MAX_FEE_CURVE_POINTS,
+};
+
+pub struct SetFeeCurveAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    pub reserve: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetFeeCurveAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, reserve, mint, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            reserve,
+            mint,
+            signers,
+        })
+    }
+}
+
+/// `[point utilizations (4 x u16)][point fees (4 x u16)][reason (u8)?]`
+pub struct SetFeeCurveInstructionData {
+    pub fee_curve_utilization_bps: [u16; MAX_FEE_CURVE_POINTS],
+    pub fee_curve_bps: [u16; MAX_FEE_CURVE_POINTS],
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for SetFeeCurveInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 16 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let mut fee_curve_utilization_bps = [0u16; MAX_FEE_CURVE_POINTS];
+        let mut fee_curve_bps = [0u16; MAX_FEE_CURVE_POINTS];
+        for i in 0..MAX_FEE_CURVE_POINTS {
+            fee_curve_utilization_bps[i] = u16::from_le_bytes([data[i * 2], data[1 + i * 2]]);
+            fee_curve_bps[i] = u16::from_le_bytes([data[8 + i * 2], data[9 + i * 2]]);
+        }
+
+        // Borrowing more of the vault never costs less: increasing utilizations, non-decreasing fees, unused points last
+        let mut previous = (0u16, 0u16);
+        for i in 0..MAX_FEE_CURVE_POINTS {
+            if fee_curve_utilization_bps[i] == 0 {
+                if fee_curve_utilization_bps[i..].iter().any(|point| *point != 0) || fee_curve_bps[i] != 0 {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                continue;
+            }
+            if fee_curve_utilization_bps[i] <= previous.0 || fee_curve_utilization_bps[i] > 10_000
+                || fee_curve_bps[i] < previous.1 || fee_curve_bps[i] > 10_000 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            previous = (fee_curve_utilization_bps[i], fee_curve_bps[i]);
+        }
+
+        Ok(Self {
+            fee_curve_utilization_bps,
+            fee_curve_bps,
+            reason: data.get(16).copied().unwrap_or(0),
+        })
+    }
+}
+
+impl SetFeeCurveInstructionData {
+    /// Whether the new curve charges less than the reserve's at any utilization, in which case
+    /// it has to go through the timelock. Both curves being piecewise linear, comparing them at
+    /// every point and at full utilization is enough.
+    pub fn loosens(&self, reserve: &Reserve) -> bool {
+        let mut updated = *reserve;
+        self.apply(&mut updated);
+
+        core::iter::once(10_000)
+            .chain(reserve.fee_curve_utilization_bps)
+            .chain(self.fee_curve_utilization_bps)
+            .any(|utilization| updated.curve_fee_bps(utilization) < reserve.curve_fee_bps(utilization))
+    }
+
+    pub fn apply(&self, reserve: &mut Reserve) {
+        reserve.fee_curve_utilization_bps = self.fee_curve_utilization_bps;
+        reserve.fee_curve_bps = self.fee_curve_bps;
+    }
+}
+
+/// records a fee curve change of `new.mint` in the audit log, emitting a FeeCurveUpdated event
+pub fn log_fee_curve_update(audit_log: &AccountInfo, pool: &Pubkey, actor: &Pubkey, old: &Reserve, new: &Reserve, reason: u8, now: i64) -> ProgramResult {
+    append_audit_entry(audit_log, pool, &AuditEntry::new(
+        *actor, AuditAction::ReserveFeeCurve, new.mint, pack_bps(old.fee_curve_bps), pack_bps(new.fee_curve_bps), reason, now,
+    ))?;
+
+    FeeCurveUpdated {
+        authority: *actor,
+        mint: new.mint,
+        fee_curve_utilization_bps: new.fee_curve_utilization_bps,
+        fee_curve_bps: new.fee_curve_bps,
+    }.emit();
+
+    Ok(())
+}
+
+pub struct SetFeeCurve<'a> {
+    pub accounts: SetFeeCurveAccounts<'a>,
+    pub instruction_data: SetFeeCurveInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetFeeCurve<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SetFeeCurveAccounts::try_from(accounts)?;
+        let instruction_data = SetFeeCurveInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetFeeCurve<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &23;
+
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        let mut reserve = load_reserve(self.accounts.reserve, self.accounts.emergency_account.key(), self.accounts.mint.key())?;
+
+        // Only raising the curve applies immediately, lowering it is queued with QueueOperation
+        if self.instruction_data.loosens(&reserve) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let old_reserve = reserve;
+        self.instruction_data.apply(&mut reserve);
+        save_reserve(self.accounts.reserve, &reserve)?;
+
+        log_fee_curve_update(
+            self.accounts.audit_log,
+            self.accounts.emergency_account.key(),
+            self.accounts.admin.key(),
+            &old_reserve,
+            &reserve,
+            self.instruction_data.reason,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+}
@@ -0,0 +1,190 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, load_emergency_state, load_reserve, pack_bps, save_reserve, verify_admin, AuditAction,
+    AuditEntry, Event, FeeTiersUpdated, Reserve, MAX_FEE_TIERS,
+};
+
+pub struct SetFeeTiersAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    pub reserve: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetFeeTiersAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, reserve, mint, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            reserve,
+            mint,
+            signers,
+        })
+    }
+}
+
+/// `[fee_bps (u16)][tier volumes (3 x u64)][tier fees (3 x u16)][reason (u8)?]`
+pub struct SetFeeTiersInstructionData {
+    pub fee_bps: u16,
+    pub fee_tier_volumes: [u64; MAX_FEE_TIERS],
+    pub fee_tier_bps: [u16; MAX_FEE_TIERS],
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for SetFeeTiersInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 32 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let fee_bps = u16::from_le_bytes([data[0], data[1]]);
+        let mut fee_tier_volumes = [0u64; MAX_FEE_TIERS];
+        let mut fee_tier_bps = [0u16; MAX_FEE_TIERS];
+        for i in 0..MAX_FEE_TIERS {
+            fee_tier_volumes[i] = u64::from_le_bytes(
+                data[2 + i * 8..10 + i * 8].try_into().map_err(|_| ProgramError::InvalidInstructionData)?
+            );
+            fee_tier_bps[i] = u16::from_le_bytes([data[26 + i * 2], data[27 + i * 2]]);
+        }
+
+        // Tiers reward volume: increasing thresholds, non-increasing fees, unused tiers last
+        let mut previous = (0u64, fee_bps);
+        for i in 0..MAX_FEE_TIERS {
+            if fee_tier_volumes[i] == 0 {
+                if fee_tier_volumes[i..].iter().any(|volume| *volume != 0) || fee_tier_bps[i] != 0 {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                continue;
+            }
+            if fee_tier_volumes[i] <= previous.0 || fee_tier_bps[i] > previous.1 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            previous = (fee_tier_volumes[i], fee_tier_bps[i]);
+        }
+
+        if fee_bps > 10_000 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            fee_bps,
+            fee_tier_volumes,
+            fee_tier_bps,
+            reason: data.get(32).copied().unwrap_or(0),
+        })
+    }
+}
+
+impl SetFeeTiersInstructionData {
+    /// Whether the new schedule charges less than the reserve's at any lifetime volume, in
+    /// which case it has to go through the timelock. Both schedules being step functions,
+    /// comparing them at every threshold is enough.
+    pub fn loosens(&self, reserve: &Reserve) -> bool {
+        let mut updated = *reserve;
+        self.apply(&mut updated);
+
+        core::iter::once(0)
+            .chain(reserve.fee_tier_volumes)
+            .chain(self.fee_tier_volumes)
+            .any(|volume| updated.borrower_fee_bps(volume) < reserve.borrower_fee_bps(volume))
+    }
+
+    pub fn apply(&self, reserve: &mut Reserve) {
+        reserve.fee_bps = self.fee_bps;
+        reserve.fee_tier_volumes = self.fee_tier_volumes;
+        reserve.fee_tier_bps = self.fee_tier_bps;
+    }
+}
+
+/// records a fee schedule change of `new.mint` in the audit log, emitting a FeeTiersUpdated event
+pub fn log_fee_tiers_update(audit_log: &AccountInfo, pool: &Pubkey, actor: &Pubkey, old: &Reserve, new: &Reserve, reason: u8, now: i64) -> ProgramResult {
+    let fees = |reserve: &Reserve| {
+        let [tier_0, tier_1, tier_2] = reserve.fee_tier_bps;
+        pack_bps([reserve.fee_bps, tier_0, tier_1, tier_2])
+    };
+    append_audit_entry(audit_log, pool, &AuditEntry::new(*actor, AuditAction::ReserveFeeTiers, new.mint, fees(old), fees(new), reason, now))?;
+
+    FeeTiersUpdated {
+        authority: *actor,
+        mint: new.mint,
+        fee_bps: new.fee_bps,
+        fee_tier_volumes: new.fee_tier_volumes,
+        fee_tier_bps: new.fee_tier_bps,
+    }.emit();
+
+    Ok(())
+}
+
+pub struct SetFeeTiers<'a> {
+    pub accounts: SetFeeTiersAccounts<'a>,
+    pub instruction_data: SetFeeTiersInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetFeeTiers<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SetFeeTiersAccounts::try_from(accounts)?;
+        let instruction_data = SetFeeTiersInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetFeeTiers<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &22;
+
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        let mut reserve = load_reserve(self.accounts.reserve, self.accounts.emergency_account.key(), self.accounts.mint.key())?;
+
+        // Only raising fees applies immediately, lowering them is queued with QueueOperation
+        if self.instruction_data.loosens(&reserve) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let old_reserve = reserve;
+        self.instruction_data.apply(&mut reserve);
+        save_reserve(self.accounts.reserve, &reserve)?;
+
+        log_fee_tiers_update(
+            self.accounts.audit_log,
+            self.accounts.emergency_account.key(),
+            self.accounts.admin.key(),
+            &old_reserve,
+            &reserve,
+            self.instruction_data.reason,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+}
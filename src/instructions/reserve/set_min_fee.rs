@@ -0,0 +1,142 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, load_emergency_state, load_reserve, save_reserve, verify_admin, AuditAction, AuditEntry, Event,
+    MinFeeUpdated, Reserve,
+};
+
+pub struct SetMinFeeAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    pub reserve: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetMinFeeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, reserve, mint, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            reserve,
+            mint,
+            signers,
+        })
+    }
+}
+
+/// `[min_fee (u64)][reason (u8)?]`
+pub struct SetMinFeeInstructionData {
+    /// Minimum fee of a loan, in the mint's base units
+    pub min_fee: u64,
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for SetMinFeeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 8 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            min_fee: u64::from_le_bytes(data[0..8].try_into().map_err(|_| ProgramError::InvalidInstructionData)?),
+            reason: data.get(8).copied().unwrap_or(0),
+        })
+    }
+}
+
+impl SetMinFeeInstructionData {
+    /// Whether the change lowers the minimum fee, in which case it has to go through the timelock
+    pub fn loosens(&self, reserve: &Reserve) -> bool {
+        self.min_fee < reserve.min_fee
+    }
+
+    pub fn apply(&self, reserve: &mut Reserve) {
+        reserve.min_fee = self.min_fee;
+    }
+}
+
+/// records a minimum fee change of `new.mint` in the audit log, emitting a MinFeeUpdated event
+pub fn log_min_fee_update(audit_log: &AccountInfo, pool: &Pubkey, actor: &Pubkey, old: &Reserve, new: &Reserve, reason: u8, now: i64) -> ProgramResult {
+    append_audit_entry(audit_log, pool, &AuditEntry::new(*actor, AuditAction::ReserveMinFee, new.mint, old.min_fee, new.min_fee, reason, now))?;
+
+    MinFeeUpdated {
+        authority: *actor,
+        mint: new.mint,
+        min_fee: new.min_fee,
+    }.emit();
+
+    Ok(())
+}
+
+pub struct SetMinFee<'a> {
+    pub accounts: SetMinFeeAccounts<'a>,
+    pub instruction_data: SetMinFeeInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetMinFee<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SetMinFeeAccounts::try_from(accounts)?;
+        let instruction_data = SetMinFeeInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetMinFee<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &24;
+
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        let mut reserve = load_reserve(self.accounts.reserve, self.accounts.emergency_account.key(), self.accounts.mint.key())?;
+
+        // Only raising the minimum fee applies immediately, lowering it is queued with QueueOperation
+        if self.instruction_data.loosens(&reserve) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let old_reserve = reserve;
+        self.instruction_data.apply(&mut reserve);
+        save_reserve(self.accounts.reserve, &reserve)?;
+
+        log_min_fee_update(
+            self.accounts.audit_log,
+            self.accounts.emergency_account.key(),
+            self.accounts.admin.key(),
+            &old_reserve,
+            &reserve,
+            self.instruction_data.reason,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+}
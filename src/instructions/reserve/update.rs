@@ -0,0 +1,183 @@
+use pinocchio::{
+    account_info::AccountInfo, 
+    program_error::ProgramError, 
+    pubkey::Pubkey, 
+    sysvars::{clock::Clock, Sysvar}, 
+    ProgramResult
+};
+
+use crate::{append_audit_entry, load_emergency_state, load_reserve, save_reserve, verify_admin, AuditAction, AuditEntry, Event, Reserve, ReserveUpdated};
+
+pub struct UpdateReserveAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    pub reserve: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for UpdateReserveAccounts<'a> {
+    type Error = ProgramError;
+    
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, reserve, mint, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            reserve,
+            mint,
+            signers,
+        })
+    }
+}
+
+pub struct UpdateReserveInstructionData {
+    pub is_enabled: bool,
+    pub max_loan_amount: u64,
+    pub max_total_outstanding: u64,
+    /// Circuit breaker threshold (`0` disabling it), left unchanged when omitted
+    pub volume_threshold: Option<u64>,
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for UpdateReserveInstructionData {
+    type Error = ProgramError;
+    
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 17 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let is_enabled = match data[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        let max_loan_amount = u64::from_le_bytes(
+            data[1..9].try_into().map_err(|_| ProgramError::InvalidInstructionData)?
+        );
+        let max_total_outstanding = u64::from_le_bytes(
+            data[9..17].try_into().map_err(|_| ProgramError::InvalidInstructionData)?
+        );
+        let (volume_threshold, reason) = match data.get(17..25) {
+            Some(threshold) => (
+                Some(u64::from_le_bytes(threshold.try_into().map_err(|_| ProgramError::InvalidInstructionData)?)),
+                data.get(25).copied().unwrap_or(0),
+            ),
+            None => (None, data.get(17).copied().unwrap_or(0)),
+        };
+        
+        Ok(Self {
+            is_enabled,
+            max_loan_amount,
+            max_total_outstanding,
+            volume_threshold,
+            reason,
+        })
+    }
+}
+
+impl UpdateReserveInstructionData {
+    /// Whether the change enables the mint or raises one of its limits, in which case it has
+    /// to go through the timelock
+    pub fn loosens(&self, reserve: &Reserve) -> bool {
+        // A disabled circuit breaker is the loosest threshold
+        let threshold = |threshold: u64| if threshold == 0 { u64::MAX } else { threshold };
+
+        (self.is_enabled && !reserve.is_enabled)
+            || self.max_loan_amount > reserve.max_loan_amount
+            || self.max_total_outstanding > reserve.max_total_outstanding
+            || self.volume_threshold.is_some_and(|volume_threshold| threshold(volume_threshold) > threshold(reserve.volume_threshold))
+    }
+
+    pub fn apply(&self, reserve: &mut Reserve) {
+        reserve.is_enabled = self.is_enabled;
+        reserve.max_loan_amount = self.max_loan_amount;
+        reserve.max_total_outstanding = self.max_total_outstanding;
+        if let Some(volume_threshold) = self.volume_threshold {
+            reserve.volume_threshold = volume_threshold;
+        }
+    }
+}
+
+/// records each reserve setting that changed between `old` and `new` in the audit log,
+/// emitting a ReserveUpdated event
+pub fn log_reserve_update(audit_log: &AccountInfo, pool: &Pubkey, actor: &Pubkey, old: &Reserve, new: &Reserve, reason: u8, now: i64) -> ProgramResult {
+    let changes = [
+        (AuditAction::ReserveEnabled, old.is_enabled as u64, new.is_enabled as u64),
+        (AuditAction::ReserveMaxLoan, old.max_loan_amount, new.max_loan_amount),
+        (AuditAction::ReserveMaxOutstanding, old.max_total_outstanding, new.max_total_outstanding),
+        (AuditAction::ReserveVolumeThreshold, old.volume_threshold, new.volume_threshold),
+    ];
+
+    for (action, old_value, new_value) in changes {
+        if old_value != new_value {
+            append_audit_entry(audit_log, pool, &AuditEntry::new(*actor, action, new.mint, old_value, new_value, reason, now))?;
+        }
+    }
+
+    ReserveUpdated::from_reserve(*actor, new).emit();
+
+    Ok(())
+}
+
+pub struct UpdateReserve<'a> {
+    pub accounts: UpdateReserveAccounts<'a>,
+    pub instruction_data: UpdateReserveInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for UpdateReserve<'a> {
+    type Error = ProgramError;
+    
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = UpdateReserveAccounts::try_from(accounts)?;
+        let instruction_data = UpdateReserveInstructionData::try_from(data)?;
+        
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> UpdateReserve<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &21;
+    
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+        
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        let mut reserve = load_reserve(self.accounts.reserve, self.accounts.emergency_account.key(), self.accounts.mint.key())?;
+
+        // Only tightening applies immediately, loosening is queued with QueueOperation
+        if self.instruction_data.loosens(&reserve) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let old_reserve = reserve;
+        self.instruction_data.apply(&mut reserve);
+        save_reserve(self.accounts.reserve, &reserve)?;
+
+        log_reserve_update(
+            self.accounts.audit_log,
+            self.accounts.emergency_account.key(),
+            self.accounts.admin.key(),
+            &old_reserve,
+            &reserve,
+            self.instruction_data.reason,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+}
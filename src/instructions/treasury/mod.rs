@@ -0,0 +1,5 @@
+pub mod set_treasury;
+pub mod sweep_fees;
+
+pub use set_treasury::*;
+pub use sweep_fees::*;
@@ -0,0 +1,149 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult
+};
+
+use crate::{
+    append_audit_entry, load_emergency_state, save_emergency_state, verify_admin, AuditAction, AuditEntry, EmergencyState, Event,
+    TreasuryUpdated,
+};
+
+pub struct SetTreasuryAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    /// Owner of the token accounts fees are swept to
+    pub treasury: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetTreasuryAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, treasury, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            treasury,
+            signers,
+        })
+    }
+}
+
+/// `[sweep_when_frozen (u8)][reason (u8)?]`
+pub struct SetTreasuryInstructionData {
+    /// Whether fees can still be swept in EmergencyMode::Frozen
+    pub sweep_when_frozen: bool,
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for SetTreasuryInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let sweep_when_frozen = match data.first() {
+            Some(0) => false,
+            Some(1) => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self {
+            sweep_when_frozen,
+            reason: data.get(1).copied().unwrap_or(0),
+        })
+    }
+}
+
+impl SetTreasuryInstructionData {
+    /// Whether the change redirects the fees to another treasury or allows sweeping them in Frozen mode,
+    /// in which case it has to go through the timelock
+    pub fn loosens(&self, state: &EmergencyState, treasury: &Pubkey) -> bool {
+        !state.has_treasury || state.treasury != *treasury || (self.sweep_when_frozen && !state.sweep_when_frozen)
+    }
+
+    pub fn apply(&self, state: &mut EmergencyState, treasury: &Pubkey) {
+        state.has_treasury = true;
+        state.treasury = *treasury;
+        state.sweep_when_frozen = self.sweep_when_frozen;
+    }
+}
+
+/// records a treasury change in the audit log, emitting a TreasuryUpdated event
+pub fn log_treasury_update(audit_log: &AccountInfo, pool: &Pubkey, actor: &Pubkey, old: &EmergencyState, new: &EmergencyState, reason: u8, now: i64) -> ProgramResult {
+    append_audit_entry(audit_log, pool, &AuditEntry::new(
+        *actor, AuditAction::SetTreasury, new.treasury, old.sweep_when_frozen as u64, new.sweep_when_frozen as u64, reason, now,
+    ))?;
+
+    TreasuryUpdated {
+        authority: *actor,
+        treasury: new.treasury,
+        sweep_when_frozen: new.sweep_when_frozen as u8,
+    }.emit();
+
+    Ok(())
+}
+
+pub struct SetTreasury<'a> {
+    pub accounts: SetTreasuryAccounts<'a>,
+    pub instruction_data: SetTreasuryInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetTreasury<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SetTreasuryAccounts::try_from(accounts)?;
+        let instruction_data = SetTreasuryInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetTreasury<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &70;
+
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        // Only disallowing sweeps in Frozen mode applies immediately, setting or changing the treasury
+        // is queued with QueueOperation
+        let treasury = self.accounts.treasury.key();
+        if self.instruction_data.loosens(&emergency_state, treasury) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let mut updated_state = emergency_state;
+        self.instruction_data.apply(&mut updated_state, treasury);
+        updated_state.last_updated = Clock::get()?.unix_timestamp;
+        save_emergency_state(self.accounts.emergency_account, &updated_state)?;
+
+        log_treasury_update(
+            self.accounts.audit_log,
+            self.accounts.emergency_account.key(),
+            self.accounts.admin.key(),
+            &emergency_state,
+            &updated_state,
+            self.instruction_data.reason,
+            updated_state.last_updated,
+        )
+    }
+}
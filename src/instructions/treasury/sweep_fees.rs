@@ -0,0 +1,177 @@
+use std::mem;
+
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult
+};
+use pinocchio_token::instructions::Transfer;
+
+use crate::{
+    append_audit_entry, get_token_mint, get_token_owner, load_emergency_state, load_reserve, save_reserve, verify_admin, verify_vault,
+    AuditAction, AuditEntry, EmergencyMode, Event, FeesSwept, PAUSE_WITHDRAW, PROTOCOL_SEED,
+};
+
+pub struct SweepFeesAccounts<'a> {
+    pub emergency_account: &'a AccountInfo,
+    pub audit_log: &'a AccountInfo,
+    pub admin: &'a AccountInfo,
+    pub protocol: &'a AccountInfo,
+    pub reserve: &'a AccountInfo,
+    /// Vault the fees accrued in, the one registered for the reserve's mint
+    pub protocol_token_account: &'a AccountInfo,
+    /// Token account of the treasury for the vault's mint
+    pub treasury_token_account: &'a AccountInfo,
+    /// Additional admin multisig signers
+    pub signers: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SweepFeesAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [emergency_account, audit_log, admin, protocol, reserve, protocol_token_account, treasury_token_account, _token_program, signers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            emergency_account,
+            audit_log,
+            admin,
+            protocol,
+            reserve,
+            protocol_token_account,
+            treasury_token_account,
+            signers,
+        })
+    }
+}
+
+/// `[bump (u8)][fee (u16)][reason (u8)?]`, the seeds of the protocol PDA owning the vault
+pub struct SweepFeesInstructionData {
+    pub bump: [u8; 1],
+    pub fee: [u8; 2],
+    /// Reason code recorded in the audit log
+    pub reason: u8,
+}
+
+impl TryFrom<&[u8]> for SweepFeesInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 1 + mem::size_of::<u16>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            bump: [data[0]],
+            fee: [data[1], data[2]],
+            reason: data.get(3).copied().unwrap_or(0),
+        })
+    }
+}
+
+pub struct SweepFees<'a> {
+    pub accounts: SweepFeesAccounts<'a>,
+    pub instruction_data: SweepFeesInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SweepFees<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SweepFeesAccounts::try_from(accounts)?;
+        let instruction_data = SweepFeesInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SweepFees<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &71;
+
+    pub fn process(&self) -> ProgramResult {
+        let emergency_state = load_emergency_state(self.accounts.emergency_account)?;
+
+        // Verify admin authority
+        verify_admin(&emergency_state, self.accounts.admin, self.accounts.signers)?;
+
+        if !emergency_state.has_treasury {
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        // Sweeps move funds out of the vaults, so they stop with withdrawals and, unless allowed, in Frozen mode
+        if emergency_state.is_instruction_paused(PAUSE_WITHDRAW)
+            || (emergency_state.emergency_mode == EmergencyMode::Frozen && !emergency_state.sweep_when_frozen)
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let mint = get_token_mint(&self.accounts.protocol_token_account.try_borrow_data()?);
+        let mut reserve = load_reserve(self.accounts.reserve, self.accounts.emergency_account.key(), &mint)?;
+        if reserve.is_instruction_paused(PAUSE_WITHDRAW) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // The accrued fees are held in the mint's registered vault, the only one it is lent from
+        verify_vault(&reserve, self.accounts.protocol_token_account)?;
+
+        // Fees only go to the configured treasury's account of the same mint
+        {
+            let treasury_data = self.accounts.treasury_token_account.try_borrow_data()?;
+            if get_token_mint(&treasury_data) != mint || get_token_owner(&treasury_data) != emergency_state.treasury {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        let amount = reserve.accrued_fees;
+        if amount == 0 {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        reserve.accrued_fees = 0;
+        save_reserve(self.accounts.reserve, &reserve)?;
+
+        let signer_seeds = [
+            Seed::from(PROTOCOL_SEED),
+            Seed::from(self.accounts.emergency_account.key()),
+            Seed::from(&self.instruction_data.fee),
+            Seed::from(&self.instruction_data.bump),
+        ];
+        let signer_seeds = [Signer::from(&signer_seeds)];
+
+        Transfer {
+            from: self.accounts.protocol_token_account,
+            to: self.accounts.treasury_token_account,
+            authority: self.accounts.protocol,
+            amount,
+        }.invoke_signed(&signer_seeds)?;
+
+        append_audit_entry(self.accounts.audit_log, self.accounts.emergency_account.key(), &AuditEntry::new(
+            *self.accounts.admin.key(),
+            AuditAction::SweepFees,
+            mint,
+            amount,
+            0,
+            self.instruction_data.reason,
+            Clock::get()?.unix_timestamp,
+        ))?;
+
+        FeesSwept {
+            authority: *self.accounts.admin.key(),
+            mint,
+            treasury_token_account: *self.accounts.treasury_token_account.key(),
+            amount,
+        }.emit();
+
+        Ok(())
+    }
+}
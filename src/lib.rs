@@ -7,6 +7,12 @@ pub use instructions::*;
 pub mod state;
 pub use state::*;
 
+pub mod error;
+pub use error::*;
+
+pub mod events;
+pub use events::*;
+
 pinocchio_pubkey::declare_id!("DSN3Ao1WRSLJXVDH68oAfSPbhU7qYKoFkN6rv2UfnEVZ");
 
 
@@ -19,9 +25,37 @@ fn process_instruction(
         Some((Loan::DISCRIMINATOR, data)) => Loan::try_from((data, accounts))?.process(),
         Some((Repay::DISCRIMINATOR, _)) => Repay::try_from(accounts)?.process(),
         Some((InitializeEmergency::DISCRIMINATOR, data)) => InitializeEmergency::try_from((data, accounts))?.process(),
-        Some((Pause::DISCRIMINATOR, _)) => Pause::try_from(accounts)?.process(),
-        Some((Unpause::DISCRIMINATOR, _)) => Unpause::try_from(accounts)?.process(),
+        Some((Pause::DISCRIMINATOR, data)) => Pause::try_from((data, accounts))?.process(),
+        Some((Unpause::DISCRIMINATOR, data)) => Unpause::try_from((data, accounts))?.process(),
         Some((SetEmergencyMode::DISCRIMINATOR, data)) => SetEmergencyMode::try_from((data, accounts))?.process(),
+        Some((UpdateLimits::DISCRIMINATOR, data)) => UpdateLimits::try_from((data, accounts))?.process(),
+        Some((TransferAdmin::DISCRIMINATOR, data)) => TransferAdmin::try_from((data, accounts))?.process(),
+        Some((AcceptAdmin::DISCRIMINATOR, _)) => AcceptAdmin::try_from(accounts)?.process(),
+        Some((SetGuardians::DISCRIMINATOR, data)) => SetGuardians::try_from((data, accounts))?.process(),
+        Some((SetAdminMultisig::DISCRIMINATOR, data)) => SetAdminMultisig::try_from((data, accounts))?.process(),
+        Some((PauseWithSignature::DISCRIMINATOR, _)) => PauseWithSignature::try_from(accounts)?.process(),
+        Some((Heartbeat::DISCRIMINATOR, data)) => Heartbeat::try_from((data, accounts))?.process(),
+        Some((TriggerDeadManSwitch::DISCRIMINATOR, _)) => TriggerDeadManSwitch::try_from(accounts)?.process(),
+        Some((RegisterReserve::DISCRIMINATOR, data)) => RegisterReserve::try_from((data, accounts))?.process(),
+        Some((UpdateReserve::DISCRIMINATOR, data)) => UpdateReserve::try_from((data, accounts))?.process(),
+        Some((SetFeeTiers::DISCRIMINATOR, data)) => SetFeeTiers::try_from((data, accounts))?.process(),
+        Some((SetFeeCurve::DISCRIMINATOR, data)) => SetFeeCurve::try_from((data, accounts))?.process(),
+        Some((SetMinFee::DISCRIMINATOR, data)) => SetMinFee::try_from((data, accounts))?.process(),
+        Some((InitializeVault::DISCRIMINATOR, data)) => InitializeVault::try_from((data, accounts))?.process(),
+        Some((QueueOperation::DISCRIMINATOR, data)) => QueueOperation::try_from((data, accounts))?.process(),
+        Some((ExecuteOperation::DISCRIMINATOR, _)) => ExecuteOperation::try_from(accounts)?.process(),
+        Some((CancelOperation::DISCRIMINATOR, _)) => CancelOperation::try_from(accounts)?.process(),
+        Some((SetKeeper::DISCRIMINATOR, data)) => SetKeeper::try_from((data, accounts))?.process(),
+        Some((UpdateAllowlist::DISCRIMINATOR, data)) => UpdateAllowlist::try_from((data, accounts))?.process(),
+        Some((SetPermissioned::DISCRIMINATOR, data)) => SetPermissioned::try_from((data, accounts))?.process(),
+        Some((SetGateProgram::DISCRIMINATOR, data)) => SetGateProgram::try_from((data, accounts))?.process(),
+        Some((SetReferralShare::DISCRIMINATOR, data)) => SetReferralShare::try_from((data, accounts))?.process(),
+        Some((ClaimReferral::DISCRIMINATOR, data)) => ClaimReferral::try_from((data, accounts))?.process(),
+        Some((RegisterReferrer::DISCRIMINATOR, data)) => RegisterReferrer::try_from((data, accounts))?.process(),
+        Some((SetTreasury::DISCRIMINATOR, data)) => SetTreasury::try_from((data, accounts))?.process(),
+        Some((SweepFees::DISCRIMINATOR, data)) => SweepFees::try_from((data, accounts))?.process(),
+        Some((SetRecovery::DISCRIMINATOR, data)) => SetRecovery::try_from((data, accounts))?.process(),
+        Some((EvacuateVault::DISCRIMINATOR, data)) => EvacuateVault::try_from((data, accounts))?.process(),
         _ => Err(ProgramError::InvalidInstructionData)
     }
 }
\ No newline at end of file
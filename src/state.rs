@@ -1,17 +1,234 @@
-use pinocchio::pubkey::Pubkey;
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::FlashLoanError;
+
+/// Seed prefix of a pool's emergency state PDA (`["emergency", pool_id]`), the pool account every
+/// other per-pool PDA is derived from
+pub const EMERGENCY_SEED: &[u8] = b"emergency";
+/// Seed prefix of the per-mint reserve PDA (`["reserve", pool, mint]`)
+pub const RESERVE_SEED: &[u8] = b"reserve";
+/// Seed prefix of the queued operation PDA (`["timelock", pool, id]`)
+pub const TIMELOCK_SEED: &[u8] = b"timelock";
+/// Seed prefix of a pool's audit log PDA (`["audit", pool]`)
+pub const AUDIT_SEED: &[u8] = b"audit";
+/// Seed prefix of the per-mint statistics PDA (`["stats", pool, mint]`)
+pub const STATS_SEED: &[u8] = b"stats";
+/// Seed prefix of the borrower history PDA (`["borrower", pool, borrower, mint]`)
+pub const BORROWER_SEED: &[u8] = b"borrower";
+/// Seed prefix of a pool's keeper whitelist PDA (`["keepers", pool]`)
+pub const KEEPERS_SEED: &[u8] = b"keepers";
+/// Seed prefix of a pool's borrower allowlist PDA (`["allowlist", pool]`)
+pub const ALLOWLIST_SEED: &[u8] = b"allowlist";
+/// Seed prefix of a referrer's per-mint referral PDA (`["referral", pool, referrer, mint]`)
+pub const REFERRAL_SEED: &[u8] = b"referral";
+/// Seed prefix of a vault token account PDA (`["vault", protocol, mint]`)
+pub const VAULT_SEED: &[u8] = b"vault";
+/// Seed prefix of a pool's protocol PDA owning its vaults (`["protocol", pool, fee, bump]`)
+pub const PROTOCOL_SEED: &[u8] = b"protocol";
+
+/// Instruction flags of the pause bitmaps, `PAUSE_DEPOSIT` and `PAUSE_WITHDRAW` gate
+/// the instructions moving funds in and out of the vaults
+pub const PAUSE_LOAN: u16 = 1 << 0;
+pub const PAUSE_REPAY: u16 = 1 << 1;
+pub const PAUSE_DEPOSIT: u16 = 1 << 2;
+pub const PAUSE_WITHDRAW: u16 = 1 << 3;
+
+/// Maximum number of guardians in the emergency state
+pub const MAX_GUARDIANS: usize = 5;
+/// Maximum number of signers of the admin multisig
+pub const MAX_ADMIN_SIGNERS: usize = 5;
+/// Delay before a pending admin can accept the transfer (24 hours)
+pub const ADMIN_TRANSFER_DELAY: i64 = 24 * 60 * 60;
+/// Delay before a queued parameter change can be executed (48 hours)
+pub const TIMELOCK_DELAY: i64 = 48 * 60 * 60;
+/// Longest pause a guardian can set, the admin ratifies it by pausing again before it expires (24 hours)
+pub const GUARDIAN_PAUSE_DURATION: i64 = 24 * 60 * 60;
+/// Minimum time in an emergency mode before de-escalating from it (6 hours)
+pub const MODE_DWELL_TIME: i64 = 6 * 60 * 60;
+/// Length of the circuit breaker's borrowed volume windows (1 hour)
+pub const VOLUME_WINDOW: i64 = 60 * 60;
+/// Number of entries kept by the audit log before the oldest are overwritten
+pub const AUDIT_LOG_CAPACITY: usize = 64;
+/// Number of loyalty fee tiers on top of a reserve's base fee
+pub const MAX_FEE_TIERS: usize = 3;
+/// Number of points of a reserve's utilization fee curve
+pub const MAX_FEE_CURVE_POINTS: usize = 4;
+/// Maximum number of keepers in the keeper whitelist
+pub const MAX_KEEPERS: usize = 16;
+/// Maximum number of borrowers in the borrower allowlist
+pub const MAX_ALLOWED_BORROWERS: usize = 64;
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
 pub struct EmergencyState {
     pub is_paused: bool,                    // Global pause state
     pub admin: Pubkey,                      // Emergency admin
-    pub max_loan_amount: u64,               // Maximum loan limit per transaction
-    pub max_total_outstanding: u64,         // Maximum total loans outstanding
     pub emergency_mode: EmergencyMode,      // Current emergency level
     pub last_updated: i64,                  // Last update timestamp
     pub has_pending_admin: bool,            // Whether there's a pending admin transfer
     pub pending_admin: Pubkey,              // Pending admin transfer (only valid if has_pending_admin is true)
     pub admin_transfer_timestamp: i64,      // Admin transfer cooldown
+    pub mode_limit_bps: [u16; 4],           // Loan limit multiplier per emergency mode (basis points)
+    pub mode_fee_surcharge_bps: [u16; 4],   // Fee surcharge per emergency mode (basis points)
+    pub paused_instructions: u16,           // Bitmap of instructions paused protocol-wide
+    pub guardians: [Pubkey; MAX_GUARDIANS], // Keys allowed to escalate (pause, raise the emergency mode)
+    pub guardian_count: u8,                 // Number of valid entries in guardians
+    pub admin_signers: [Pubkey; MAX_ADMIN_SIGNERS], // Admin multisig signers
+    pub admin_signer_count: u8,             // Number of valid entries in admin_signers
+    pub admin_threshold: u8,                // Signatures required, 0 when the admin key acts alone
+    pub pause_nonces: [u64; MAX_GUARDIANS], // Last nonce consumed by each guardian slot's signed pauses
+    pub pause_expiry: i64,                  // When the protocol-wide pause lifts, 0 if indefinite
+    pub last_heartbeat: i64,                // Last admin heartbeat
+    pub heartbeat_interval: i64,            // Silence after which the dead-man switch can be triggered, 0 if disabled
+    pub dead_man_mode: EmergencyMode,       // Mode the dead-man switch escalates to
+    pub is_permissioned: bool,              // Whether only allowlisted borrowers can borrow
+    pub has_gate_program: bool,             // Whether Loan asks a gate program to approve each loan
+    pub gate_program: Pubkey,               // Gate program (only valid if has_gate_program is true)
+    pub referral_share_bps: u16,            // Share of a referred loan's fee credited to its referrer (basis points)
+    pub has_treasury: bool,                 // Whether accrued fees can be swept to the treasury
+    pub treasury: Pubkey,                   // Owner of the treasury token accounts (only valid if has_treasury is true)
+    pub sweep_when_frozen: bool,            // Whether fees can still be swept in EmergencyMode::Frozen
+    pub pool_id: u64,                       // Pool id seeding this emergency state PDA
+    pub has_recovery: bool,                 // Whether vaults can be evacuated in EmergencyMode::Frozen
+    pub recovery: Pubkey,                   // Owner of the token accounts vaults are evacuated to (only valid if has_recovery is true)
+    pub mode_changed_at: i64,               // Last emergency mode transition, the de-escalation dwell time counting from it
+    pub pause_nonce_floor: u64,             // Highest signed pause nonce consumed by any guardian, the starting nonce of added guardians
+}
+
+impl EmergencyState {
+    /// Default degradation: Limited = 1/2, Emergency = 1/4 of the normal limits, Frozen = nothing
+    pub const DEFAULT_MODE_LIMIT_BPS: [u16; 4] = [10_000, 5_000, 2_500, 0];
+
+    /// Loan limit once the current emergency mode's multiplier is applied
+    pub fn mode_loan_limit(&self, max_loan_amount: u64) -> u64 {
+        let limit_bps = self.mode_limit_bps[self.emergency_mode as usize];
+        (max_loan_amount as u128 * limit_bps as u128 / 10_000) as u64
+    }
+
+    /// Fee surcharge of the current emergency mode
+    pub fn mode_fee_surcharge(&self) -> u16 {
+        self.mode_fee_surcharge_bps[self.emergency_mode as usize]
+    }
+
+    /// Part of a referred loan's fee credited to its referrer, rounded down
+    pub fn referral_share(&self, fee: u64) -> u64 {
+        (fee as u128 * self.referral_share_bps as u128 / 10_000) as u64
+    }
+
+    /// Whether the instruction is paused by the global pause or its bit in the bitmap,
+    /// expired pauses being lifted by `load_emergency_state`
+    pub fn is_instruction_paused(&self, instruction: u16) -> bool {
+        self.is_paused || self.paused_instructions & instruction != 0
+    }
+
+    /// Emergency mode state machine: escalating is always allowed, de-escalating goes down a
+    /// single level once the mode has been held for `MODE_DWELL_TIME`
+    pub fn check_mode_transition(&self, mode: EmergencyMode, now: i64) -> Result<(), FlashLoanError> {
+        let (from, to) = (self.emergency_mode as u8, mode as u8);
+
+        if to == from {
+            return Err(FlashLoanError::ModeUnchanged);
+        }
+        if to > from {
+            return Ok(());
+        }
+        if from - to > 1 {
+            return Err(FlashLoanError::ModeSkipsLevel);
+        }
+        if now < self.mode_changed_at + MODE_DWELL_TIME {
+            return Err(FlashLoanError::ModeCooldownActive);
+        }
+
+        Ok(())
+    }
+
+    /// Whether the admin has been silent for longer than the heartbeat interval
+    pub fn is_heartbeat_expired(&self, now: i64) -> bool {
+        self.heartbeat_interval != 0 && now > self.last_heartbeat.saturating_add(self.heartbeat_interval)
+    }
+
+    /// Moves to `mode`, restarting the dwell time when it changes. Mode transitions are the only
+    /// writers of `mode_changed_at`, other updates can't hold off a de-escalation.
+    pub fn set_mode(&mut self, mode: EmergencyMode, now: i64) {
+        if mode != self.emergency_mode {
+            self.emergency_mode = mode;
+            self.mode_changed_at = now;
+        }
+    }
+
+    /// Moves the emergency mode one level toward Frozen
+    pub fn escalate(&mut self, now: i64) {
+        let mode = match self.emergency_mode {
+            EmergencyMode::Normal => EmergencyMode::Limited,
+            EmergencyMode::Limited => EmergencyMode::Emergency,
+            EmergencyMode::Emergency | EmergencyMode::Frozen => EmergencyMode::Frozen,
+        };
+        self.set_mode(mode, now);
+    }
+
+    /// Lifts the protocol-wide pause once its expiry has passed
+    pub fn expire_pause(&mut self, now: i64) {
+        if self.pause_expiry != 0 && now >= self.pause_expiry {
+            self.is_paused = false;
+            self.paused_instructions = 0;
+            self.pause_expiry = 0;
+        }
+    }
+
+    /// Pauses `instructions` protocol-wide (everything when 0) until `expiry`, 0 pausing
+    /// indefinitely. A pause already in effect is only ever extended, never shortened.
+    pub fn pause(&mut self, instructions: u16, expiry: i64, now: i64) {
+        self.expire_pause(now);
+        let was_paused = self.is_paused || self.paused_instructions != 0;
+
+        if instructions == 0 {
+            self.is_paused = true;
+        } else {
+            self.paused_instructions |= instructions;
+        }
+
+        self.pause_expiry = if !was_paused {
+            expiry
+        } else if self.pause_expiry == 0 || expiry == 0 {
+            0
+        } else {
+            self.pause_expiry.max(expiry)
+        };
+    }
+
+    pub fn is_guardian(&self, key: &Pubkey) -> bool {
+        self.guardian_slot(key).is_some()
+    }
+
+    /// Index of the guardian in `guardians`, which also indexes its signed pause nonce
+    pub fn guardian_slot(&self, key: &Pubkey) -> Option<usize> {
+        self.guardians[..self.guardian_count as usize].iter().position(|guardian| guardian == key)
+    }
+
+    /// Replaces the guardian set. Guardians kept in the new set keep their signed pause nonce wherever their
+    /// slot moves, added ones (re-added ones included) start from the highest nonce ever consumed so that
+    /// their old messages can't be replayed.
+    pub fn replace_guardians(&mut self, guardians: [Pubkey; MAX_GUARDIANS], guardian_count: u8) {
+        let old = *self;
+        self.guardians = guardians;
+        self.guardian_count = guardian_count;
+        self.pause_nonces = [old.pause_nonce_floor; MAX_GUARDIANS];
+        for (slot, guardian) in guardians[..guardian_count as usize].iter().enumerate() {
+            if let Some(old_slot) = old.guardian_slot(guardian) {
+                self.pause_nonces[slot] = old.pause_nonces[old_slot];
+            }
+        }
+    }
+
+    /// Consumes a guardian's signed pause nonce, raising the floor of later added guardians
+    pub fn consume_pause_nonce(&mut self, slot: usize, nonce: u64) {
+        self.pause_nonces[slot] = nonce;
+        self.pause_nonce_floor = self.pause_nonce_floor.max(nonce);
+    }
+
+    pub fn is_admin_signer(&self, key: &Pubkey) -> bool {
+        self.admin_signers[..self.admin_signer_count as usize].contains(key)
+    }
 }
 
 #[repr(u8)]
@@ -21,4 +238,479 @@ pub enum EmergencyMode {
     Limited = 1,       // Limited operations (reduced limits)
     Emergency = 2,     // Emergency mode (minimal operations)
     Frozen = 3,        // Completely frozen (only repay allowed)
-}
\ No newline at end of file
+}
+
+impl TryFrom<u8> for EmergencyMode {
+    type Error = ProgramError;
+
+    fn try_from(mode: u8) -> Result<Self, Self::Error> {
+        match mode {
+            0 => Ok(Self::Normal),
+            1 => Ok(Self::Limited),
+            2 => Ok(Self::Emergency),
+            3 => Ok(Self::Frozen),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+/// Per-mint registry entry, a PDA seeded by `["reserve", pool, mint]`.
+/// Limits are expressed in the mint's base units.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Reserve {
+    pub mint: Pubkey,                       // Mint this reserve governs
+    pub max_loan_amount: u64,               // Maximum loan limit per transaction
+    pub max_total_outstanding: u64,         // Maximum amount lent out at once
+    pub total_outstanding: u64,             // Amount currently lent out
+    pub is_enabled: bool,                   // Whether loans are allowed for this mint
+    pub decimals: u8,                       // Mint decimals, copied from the mint at registration
+    pub bump: u8,                           // Reserve PDA bump
+    pub paused_instructions: u16,           // Bitmap of instructions paused for this mint
+    pub volume_threshold: u64,              // Borrowed volume per window tripping the circuit breaker, 0 if disabled
+    pub volume_window_start: i64,           // Start of the current volume window
+    pub volume_current: u64,                // Volume borrowed in the current window
+    pub volume_previous: u64,               // Volume borrowed in the previous window
+    pub fee_tier_volumes: [u64; MAX_FEE_TIERS], // Borrower lifetime volume from which each tier applies, 0 if unused
+    pub fee_bps: u16,                       // Base fee (basis points)
+    pub fee_tier_bps: [u16; MAX_FEE_TIERS], // Fee of each loyalty tier (basis points)
+    pub fee_curve_utilization_bps: [u16; MAX_FEE_CURVE_POINTS], // Vault share borrowed at each curve point, 0 if unused
+    pub fee_curve_bps: [u16; MAX_FEE_CURVE_POINTS], // Fee added at each curve point (basis points)
+    pub min_fee: u64,                       // Minimum fee of a loan, in the mint's base units
+    pub accrued_fees: u64,                  // Treasury's share of repaid fees not swept yet
+    pub pause_expiry: i64,                  // When the mint's pause lifts, 0 if indefinite
+    pub vault: Pubkey,                      // Vault created by InitializeVault, the only one the mint is lent from, default until then
+}
+
+impl Reserve {
+    /// Base fee of newly registered reserves (basis points)
+    pub const DEFAULT_FEE_BPS: u16 = 9;
+
+    /// Whether the instruction is paused for this mint
+    pub fn is_instruction_paused(&self, instruction: u16) -> bool {
+        self.paused_instructions & instruction != 0
+    }
+
+    /// Lifts the mint's pause once its expiry has passed
+    pub fn expire_pause(&mut self, now: i64) {
+        if self.pause_expiry != 0 && now >= self.pause_expiry {
+            self.paused_instructions = 0;
+            self.pause_expiry = 0;
+        }
+    }
+
+    /// Pauses `instructions` for this mint (everything when 0) until `expiry`, 0 pausing
+    /// indefinitely. Like the protocol-wide pause, a pause in effect is only ever extended.
+    pub fn pause(&mut self, instructions: u16, expiry: i64, now: i64) {
+        self.expire_pause(now);
+        let was_paused = self.paused_instructions != 0;
+
+        self.paused_instructions |= if instructions == 0 { u16::MAX } else { instructions };
+
+        self.pause_expiry = if !was_paused {
+            expiry
+        } else if self.pause_expiry == 0 || expiry == 0 {
+            0
+        } else {
+            self.pause_expiry.max(expiry)
+        };
+    }
+
+    /// Fee of a borrower who already borrowed `lifetime_volume` of this mint (basis points),
+    /// the highest tier reached applying
+    pub fn borrower_fee_bps(&self, lifetime_volume: u64) -> u16 {
+        (0..MAX_FEE_TIERS)
+            .take_while(|i| self.fee_tier_volumes[*i] != 0)
+            .filter(|i| lifetime_volume >= self.fee_tier_volumes[*i])
+            .last()
+            .map_or(self.fee_bps, |i| self.fee_tier_bps[i])
+    }
+
+    /// Fee added for borrowing `utilization_bps` of the vault (basis points), interpolated
+    /// linearly between the curve points from no fee at zero utilization, flat past the last point
+    pub fn curve_fee_bps(&self, utilization_bps: u16) -> u16 {
+        let mut previous = (0u16, 0u16);
+        for (point, fee) in self.fee_curve_utilization_bps.iter().zip(self.fee_curve_bps) {
+            if *point == 0 {
+                break;
+            }
+            if utilization_bps <= *point {
+                let slope = (fee - previous.1) as u32 * (utilization_bps - previous.0) as u32;
+                return previous.1 + (slope / (*point - previous.0) as u32) as u16;
+            }
+            previous = (*point, fee);
+        }
+        previous.1
+    }
+
+    /// Fee of borrowing `amount` from a vault holding `vault_balance`, for a borrower who already
+    /// borrowed `lifetime_volume` of this mint. Loan charges exactly this, clients can call it to quote loans.
+    pub fn quote_fee(&self, emergency_state: &EmergencyState, lifetime_volume: u64, vault_balance: u64, amount: u64) -> Result<u64, ProgramError> {
+        let fee_bps = self.borrower_fee_bps(lifetime_volume) as u64
+            + self.curve_fee_bps(utilization_bps(amount, vault_balance)) as u64
+            + emergency_state.mode_fee_surcharge() as u64;
+        fee_amount(amount, fee_bps, self.min_fee)
+    }
+
+    /// Borrowed volume over the last `VOLUME_WINDOW`, the previous window weighted by the
+    /// share of it still inside the rolling window
+    pub fn window_volume(&self, now: i64) -> u64 {
+        let elapsed = now - self.volume_window_start;
+        if elapsed >= 2 * VOLUME_WINDOW {
+            return 0;
+        }
+        if elapsed >= VOLUME_WINDOW {
+            return (self.volume_current as u128 * (2 * VOLUME_WINDOW - elapsed) as u128 / VOLUME_WINDOW as u128) as u64;
+        }
+
+        let previous = self.volume_previous as u128 * (VOLUME_WINDOW - elapsed) as u128 / VOLUME_WINDOW as u128;
+        self.volume_current.saturating_add(previous as u64)
+    }
+
+    /// Records `amount` borrowed at `now`, returning whether it pushed the rolling volume over
+    /// the circuit breaker threshold
+    pub fn record_volume(&mut self, amount: u64, now: i64) -> bool {
+        let before = self.window_volume(now);
+
+        let window_start = now - now.rem_euclid(VOLUME_WINDOW);
+        if window_start != self.volume_window_start {
+            self.volume_previous = if window_start - self.volume_window_start == VOLUME_WINDOW { self.volume_current } else { 0 };
+            self.volume_current = 0;
+            self.volume_window_start = window_start;
+        }
+        self.volume_current = self.volume_current.saturating_add(amount);
+
+        self.volume_threshold != 0 && before <= self.volume_threshold && self.window_volume(now) > self.volume_threshold
+    }
+}
+
+/// Cumulative loan statistics of a mint, created alongside its reserve
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct MintStats {
+    pub mint: Pubkey,                       // Mint these statistics cover
+    pub total_borrowed: u64,                // Cumulative borrowed volume
+    pub total_fees: u64,                    // Cumulative fees collected on repayment
+    pub loan_count: u64,                    // Number of loans taken
+    pub largest_loan: u64,                  // Largest single loan
+    pub bump: u8,                           // Stats PDA bump
+}
+
+impl MintStats {
+    /// Records a loan of `amount`, the counters saturating rather than failing the loan
+    pub fn record_loan(&mut self, amount: u64) {
+        self.total_borrowed = self.total_borrowed.saturating_add(amount);
+        self.loan_count = self.loan_count.saturating_add(1);
+        self.largest_loan = self.largest_loan.max(amount);
+    }
+
+    /// Records the fee paid on a repayment
+    pub fn record_repay(&mut self, fee: u64) {
+        self.total_fees = self.total_fees.saturating_add(fee);
+    }
+}
+
+/// Lifetime activity of a borrower on a mint, driving its loyalty fee tier
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct BorrowerHistory {
+    pub borrower: Pubkey,                   // Borrower this history belongs to
+    pub mint: Pubkey,                       // Mint borrowed
+    pub total_borrowed: u64,                // Lifetime borrowed volume
+    pub loan_count: u64,                    // Number of loans taken
+    pub total_fees: u64,                    // Lifetime fees paid
+    pub bump: u8,                           // Borrower history PDA bump
+}
+
+impl BorrowerHistory {
+    pub fn record_loan(&mut self, amount: u64) {
+        self.total_borrowed = self.total_borrowed.saturating_add(amount);
+        self.loan_count = self.loan_count.saturating_add(1);
+    }
+
+    pub fn record_repay(&mut self, fee: u64) {
+        self.total_fees = self.total_fees.saturating_add(fee);
+    }
+}
+
+/// Referral fees a referrer earned on a mint, created by the admin's RegisterReferrer before loans can name the referrer
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Referral {
+    pub referrer: Pubkey,                   // Referrer credited
+    pub mint: Pubkey,                       // Mint of the credited fees
+    pub unclaimed: u64,                     // Fees credited and not claimed yet
+    pub total_earned: u64,                  // Lifetime fees credited
+    pub bump: u8,                           // Referral PDA bump
+}
+
+impl Referral {
+    pub fn credit(&mut self, amount: u64) {
+        self.unclaimed = self.unclaimed.saturating_add(amount);
+        self.total_earned = self.total_earned.saturating_add(amount);
+    }
+}
+
+/// Whitelisted borrower key, or program whose instruction invokes Loan, borrowing at its own
+/// fee and above the reserves' per-loan limit
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keeper {
+    pub key: Pubkey,                        // Borrower or calling program
+    pub fee_bps: u16,                       // Fee replacing the reserve's tiers, curve and minimum fee (basis points)
+    pub limit_bps: u16,                     // Per-loan limit multiplier, at least 10_000 (basis points)
+}
+
+impl Keeper {
+    /// Fee of a keeper loan of `amount`, the emergency mode surcharge still applying
+    pub fn quote_fee(&self, emergency_state: &EmergencyState, amount: u64) -> Result<u64, ProgramError> {
+        fee_amount(amount, self.fee_bps as u64 + emergency_state.mode_fee_surcharge() as u64, 0)
+    }
+
+    /// Per-loan limit of the keeper on a reserve allowing `max_loan_amount`
+    pub fn loan_limit(&self, max_loan_amount: u64) -> u64 {
+        (max_loan_amount as u128 * self.limit_bps as u128 / 10_000).min(u64::MAX as u128) as u64
+    }
+
+    /// Value of the audit log, `fee_bps | limit_bps << 16`
+    pub fn audit_value(keeper: Option<&Keeper>) -> u64 {
+        keeper.map_or(0, |keeper| keeper.fee_bps as u64 | (keeper.limit_bps as u64) << 16)
+    }
+}
+
+/// Keeper whitelist PDA seeded by `["keepers", pool]`, created with the pool's emergency state
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct KeeperWhitelist {
+    pub keepers: [Keeper; MAX_KEEPERS],     // Whitelisted keepers
+    pub keeper_count: u8,                   // Number of valid entries in keepers
+    pub bump: u8,                           // Keeper whitelist PDA bump
+}
+
+impl KeeperWhitelist {
+    pub fn find(&self, key: &Pubkey) -> Option<&Keeper> {
+        self.keepers[..self.keeper_count as usize].iter().find(|keeper| keeper.key == *key)
+    }
+
+    /// Adds or updates the keeper, or removes `key` when `keeper` is `None`
+    pub fn set(&mut self, key: &Pubkey, keeper: Option<Keeper>) -> Result<(), ProgramError> {
+        let count = self.keeper_count as usize;
+        let index = self.keepers[..count].iter().position(|entry| entry.key == *key);
+        match (index, keeper) {
+            (Some(index), Some(keeper)) => self.keepers[index] = keeper,
+            (None, Some(keeper)) => {
+                if count == MAX_KEEPERS {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                self.keepers[count] = keeper;
+                self.keeper_count += 1;
+            }
+            (Some(index), None) => {
+                self.keepers[index] = self.keepers[count - 1];
+                self.keepers[count - 1] = Keeper { key: Pubkey::default(), fee_bps: 0, limit_bps: 0 };
+                self.keeper_count -= 1;
+            }
+            (None, None) => {}
+        }
+        Ok(())
+    }
+}
+
+/// Borrowers allowed to borrow while the pool is permissioned, created by the first UpdateAllowlist
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct BorrowerAllowlist {
+    pub borrowers: [Pubkey; MAX_ALLOWED_BORROWERS], // Allowed borrowers
+    pub borrower_count: u8,                 // Number of valid entries in borrowers
+    pub bump: u8,                           // Borrower allowlist PDA bump
+}
+
+impl BorrowerAllowlist {
+    pub fn is_allowed(&self, borrower: &Pubkey) -> bool {
+        self.borrowers[..self.borrower_count as usize].contains(borrower)
+    }
+
+    /// Adds or removes the borrower
+    pub fn set(&mut self, borrower: &Pubkey, is_allowed: bool) -> Result<(), ProgramError> {
+        let count = self.borrower_count as usize;
+        let index = self.borrowers[..count].iter().position(|entry| entry == borrower);
+        match (index, is_allowed) {
+            (None, true) => {
+                if count == MAX_ALLOWED_BORROWERS {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                self.borrowers[count] = *borrower;
+                self.borrower_count += 1;
+            }
+            (Some(index), false) => {
+                self.borrowers[index] = self.borrowers[count - 1];
+                self.borrowers[count - 1] = Pubkey::default();
+                self.borrower_count -= 1;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimelockAction {
+    UpdateLimits = 0,      // Payload of UpdateLimits
+    UpdateReserve = 1,     // Payload of UpdateReserve, target being the mint
+    SetEmergencyMode = 2,  // Payload of SetEmergencyMode
+    SetFeeTiers = 3,       // Payload of SetFeeTiers, target being the mint
+    SetFeeCurve = 4,       // Payload of SetFeeCurve, target being the mint
+    SetMinFee = 5,         // Payload of SetMinFee, target being the mint
+    SetKeeper = 6,         // Payload of SetKeeper, target being the keeper
+    SetPermissioned = 7,   // Payload of SetPermissioned
+    SetGateProgram = 8,    // Payload of SetGateProgram, target being the gate program
+    SetReferralShare = 9,  // Payload of SetReferralShare
+    SetTreasury = 10,      // Payload of SetTreasury, target being the treasury
+    SetRecovery = 11,      // Payload of SetRecovery, target being the recovery address
+    SetDeadManSwitch = 12, // Payload of Heartbeat with the new switch settings
+}
+
+impl TryFrom<u8> for TimelockAction {
+    type Error = ProgramError;
+
+    fn try_from(action: u8) -> Result<Self, Self::Error> {
+        match action {
+            0 => Ok(Self::UpdateLimits),
+            1 => Ok(Self::UpdateReserve),
+            2 => Ok(Self::SetEmergencyMode),
+            3 => Ok(Self::SetFeeTiers),
+            4 => Ok(Self::SetFeeCurve),
+            5 => Ok(Self::SetMinFee),
+            6 => Ok(Self::SetKeeper),
+            7 => Ok(Self::SetPermissioned),
+            8 => Ok(Self::SetGateProgram),
+            9 => Ok(Self::SetReferralShare),
+            10 => Ok(Self::SetTreasury),
+            11 => Ok(Self::SetRecovery),
+            12 => Ok(Self::SetDeadManSwitch),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+/// Parameter change queued by the admin, a PDA seeded by `["timelock", pool, id]`.
+/// Executable by anyone once `eta` has passed, cancellable by the admin or a guardian.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct TimelockOperation {
+    pub id: u64,                            // Operation id chosen by the admin
+    pub eta: i64,                           // Earliest execution timestamp
+    pub payer: Pubkey,                      // Refunded when the operation is executed or cancelled
+    pub target: Pubkey,                     // Mint of reserve updates, keeper of keeper updates, unused otherwise
+    pub action: TimelockAction,             // Change to apply
+    pub bump: u8,                           // Operation PDA bump
+    pub payload_len: u8,                    // Number of valid bytes in payload
+    pub payload: [u8; 32],                  // Instruction data of the change
+}
+
+impl TimelockOperation {
+    pub fn payload(&self) -> &[u8] {
+        &self.payload[..self.payload_len as usize]
+    }
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AuditAction {
+    Pause = 0,                  // Pause bitmap (bit 16 for the global pause), target mint for a single mint
+    Unpause = 1,                // Same values as Pause
+    SetEmergencyMode = 2,       // Emergency mode
+    UpdateLimits = 3,           // Mode limit bps, packed as 4 x u16
+    UpdateFeeSurcharges = 4,    // Mode fee surcharge bps, packed as 4 x u16
+    ReserveEnabled = 5,         // Reserve is_enabled, target mint
+    ReserveMaxLoan = 6,         // Reserve max_loan_amount, target mint
+    ReserveMaxOutstanding = 7,  // Reserve max_total_outstanding, target mint
+    ReserveVolumeThreshold = 8, // Reserve volume_threshold, target mint
+    RegisterReserve = 9,        // New max_loan_amount, target mint
+    TransferAdmin = 10,         // Target pending admin
+    AcceptAdmin = 11,           // Target previous admin
+    SetGuardians = 12,          // Guardian count
+    SetAdminMultisig = 13,      // Threshold << 8 | signer count
+    QueueOperation = 14,        // New value operation id, old value its action, target operation
+    ExecuteOperation = 15,      // Same values as QueueOperation
+    CancelOperation = 16,       // Same values as QueueOperation
+    DeadManSwitch = 17,         // Emergency mode
+    ReserveFeeTiers = 18,       // Base and tier fee bps, packed as 4 x u16, target mint
+    ReserveFeeCurve = 19,       // Curve point fee bps, packed as 4 x u16, target mint
+    ReserveMinFee = 20,         // Reserve min_fee, target mint
+    SetKeeper = 21,             // Keeper fee bps | limit bps << 16, 0 if not whitelisted, target keeper
+    SetPermissioned = 22,       // Emergency state is_permissioned
+    UpdateAllowlist = 23,       // Whether the target borrower is allowlisted
+    SetGateProgram = 24,        // Emergency state has_gate_program, target gate program
+    SetReferralShare = 25,      // Emergency state referral_share_bps
+    SetTreasury = 26,           // Emergency state sweep_when_frozen, target treasury
+    SweepFees = 27,             // Reserve accrued fees before and after the sweep, target mint
+    SetRecovery = 28,           // Emergency state has_recovery, target recovery address
+    EvacuateVault = 29,         // Vault balance before the evacuation, target mint
+    SetHeartbeatInterval = 30,  // Emergency state heartbeat_interval in seconds
+    SetDeadManMode = 31,        // Emergency state dead_man_mode
+    RegisterReferrer = 32,      // Target referral PDA of the registered referrer and mint
+}
+
+/// Audit log entry. Changes applied by a queued operation are logged with the operation
+/// account as actor, permissionless cranks with the default pubkey.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct AuditEntry {
+    pub actor: Pubkey,                      // Signer of the action
+    pub target: Pubkey,                     // Key the action applies to, if any
+    pub timestamp: i64,                     // When the action happened
+    pub old_value: u64,                     // Value before the action, see AuditAction
+    pub new_value: u64,                     // Value after the action, see AuditAction
+    pub action: AuditAction,                // Action performed
+    pub reason: u8,                         // Reason code given by the actor, 0 if none
+}
+
+impl AuditEntry {
+    pub fn new(actor: Pubkey, action: AuditAction, target: Pubkey, old_value: u64, new_value: u64, reason: u8, timestamp: i64) -> Self {
+        Self { actor, target, timestamp, old_value, new_value, action, reason }
+    }
+}
+
+/// Header of the audit log PDA, followed by a ring buffer of `AUDIT_LOG_CAPACITY` entries
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct AuditLogHeader {
+    pub count: u64,                         // Entries ever appended, the next one going to count % capacity
+    pub bump: u8,                           // Audit log PDA bump
+}
+
+impl AuditLogHeader {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+    pub const ACCOUNT_LEN: usize = Self::LEN + AUDIT_LOG_CAPACITY * core::mem::size_of::<AuditEntry>();
+
+    /// Offset in the account of the entry with sequence number `count`
+    pub fn entry_offset(count: u64) -> usize {
+        Self::LEN + (count % AUDIT_LOG_CAPACITY as u64) as usize * core::mem::size_of::<AuditEntry>()
+    }
+}
+
+/// Share of a vault holding `vault_balance` that borrowing `amount` takes (basis points), capped at 100%
+pub fn utilization_bps(amount: u64, vault_balance: u64) -> u16 {
+    if vault_balance == 0 {
+        return 10_000;
+    }
+    (amount as u128 * 10_000 / vault_balance as u128).min(10_000) as u16
+}
+
+/// Fee of `fee_bps` on `amount`, rounded up in the protocol's favour and at least `min_fee`
+pub fn fee_amount(amount: u64, fee_bps: u64, min_fee: u64) -> Result<u64, ProgramError> {
+    let fee = (amount as u128 * fee_bps as u128).div_ceil(10_000).max(min_fee as u128);
+    u64::try_from(fee).map_err(|_| ProgramError::ArithmeticOverflow)
+}
+
+/// Pause state of the audit log: the bitmap, with the global pause as bit 16
+pub fn pause_bits(is_paused: bool, paused_instructions: u16) -> u64 {
+    (is_paused as u64) << 16 | paused_instructions as u64
+}
+
+/// Four u16 values packed into an audit log value
+pub fn pack_bps(bps: [u16; 4]) -> u64 {
+    bps.iter().enumerate().fold(0, |packed, (i, bps)| packed | (*bps as u64) << (i * 16))
+}
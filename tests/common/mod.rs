@@ -0,0 +1,46 @@
+//! Accounts and keys shared by the test suites
+#![allow(dead_code)]
+
+use mollusk_svm::Mollusk;
+use solana_sdk::account::Account;
+use solana_sdk::pubkey;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+
+use pinocchio_flash_loan::{AuditLogHeader, EmergencyState, ID};
+
+pub const PROGRAM: Pubkey = Pubkey::new_from_array(ID);
+pub const ADMIN: Pubkey = pubkey!("Bv1vrbzogVpKNW2iRYJXLRUEVv6gD8xd9gid1Yh6hoiQ");
+
+pub fn mollusk() -> Mollusk {
+    Mollusk::new(&PROGRAM, "target/deploy/pinocchio_flash_loan")
+}
+
+/// emergency state PDA of the default pool, every other PDA of the pool being derived from it
+pub fn pool() -> Pubkey {
+    Pubkey::find_program_address(&[b"emergency", &0u64.to_le_bytes()], &PROGRAM).0
+}
+
+/// create the audit log PDA account with an empty ring buffer
+pub fn create_audit_log_account() -> (Pubkey, Account) {
+    let (audit_log, bump) = Pubkey::find_program_address(&[b"audit", pool().as_ref()], &PROGRAM);
+    let mut data = vec![0u8; AuditLogHeader::ACCOUNT_LEN];
+    data[8] = bump; // bump, after the entry count
+    (audit_log, Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: PROGRAM,
+        executable: false,
+        rent_epoch: 0,
+    })
+}
+
+/// create emergency state account data, unpaused and in normal mode
+pub fn create_emergency_state_data(admin: &Pubkey) -> Vec<u8> {
+    let mut data = vec![0u8; 640]; // EmergencyState size
+    data[1..33].copy_from_slice(&admin.to_bytes());
+    for (i, bps) in EmergencyState::DEFAULT_MODE_LIMIT_BPS.iter().enumerate() {
+        data[96 + i * 2..98 + i * 2].copy_from_slice(&bps.to_le_bytes()); // mode_limit_bps
+    }
+    data
+}
@@ -8,66 +8,74 @@ use solana_sdk::pubkey::Pubkey as SolanaPubkey;
 use solana_sdk::rent::Rent;
 
 use pinocchio::pubkey::Pubkey;
-use pinocchio_flash_loan::{ID, state::EmergencyMode};
+use pinocchio_flash_loan::{read_ed25519_signature, FlashLoanError, PauseInstructionData, PauseMessage, UnpauseInstructionData, ID, state::{pack_bps, pause_bits, AuditEntry, AuditLogHeader, AUDIT_LOG_CAPACITY, MODE_DWELL_TIME, EmergencyMode, EmergencyState, Reserve, PAUSE_LOAN, PAUSE_REPAY, PAUSE_WITHDRAW}};
 
 pub const PROGRAM: SolanaPubkey = SolanaPubkey::new_from_array(ID);
 pub const ADMIN: SolanaPubkey = pubkey!("Bv1vrbzogVpKNW2iRYJXLRUEVv6gD8xd9gid1Yh6hoiQ");
 pub const PAYER: SolanaPubkey = pubkey!("HZ7znC4EWr3EQm2kNTu8XWKhgfzEhPqhXFqZNm8RhyuR");
+pub const GUARDIAN: SolanaPubkey = pubkey!("9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin");
 
 pub fn mollusk() -> Mollusk {
     Mollusk::new(&PROGRAM, "target/deploy/pinocchio_flash_loan")
 }
 
+/// emergency state PDA of the default pool, every other PDA of the pool being derived from it
+fn pool() -> SolanaPubkey {
+    SolanaPubkey::find_program_address(&[b"emergency", &0u64.to_le_bytes()], &PROGRAM).0
+}
+
+/// create the audit log PDA account with an empty ring buffer
+fn create_audit_log_account() -> (SolanaPubkey, Account) {
+    let (audit_log, bump) = SolanaPubkey::find_program_address(&[b"audit", pool().as_ref()], &PROGRAM);
+    let mut data = vec![0u8; AuditLogHeader::ACCOUNT_LEN];
+    data[8] = bump; // bump, after the entry count
+    (audit_log, Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: PROGRAM,
+        executable: false,
+        rent_epoch: 0,
+    })
+}
+
 /// create emergency state account data
+#[allow(clippy::too_many_arguments)]
 fn create_emergency_state_data(
     is_paused: bool,
     admin: &Pubkey,
-    max_loan_amount: u64,
-    max_total_outstanding: u64,
     emergency_mode: EmergencyMode,
     last_updated: i64,
     has_pending_admin: bool,
     pending_admin: &Pubkey,
     admin_transfer_timestamp: i64,
 ) -> Vec<u8> {
-    let mut data = vec![0u8; 113]; // EmergencyState size
-    
-    let mut offset = 0;
+    let mut data = vec![0u8; 640]; // EmergencyState size (repr(C), including padding)
     
     // is_paused (1 byte)
-    data[offset] = if is_paused { 1 } else { 0 };
-    offset += 1;
+    data[0] = if is_paused { 1 } else { 0 };
     
     // admin (32 bytes)
-    data[offset..offset + 32].copy_from_slice(admin);
-    offset += 32;
-    
-    // max_loan_amount (8 bytes)
-    data[offset..offset + 8].copy_from_slice(&max_loan_amount.to_le_bytes());
-    offset += 8;
-    
-    // max_total_outstanding (8 bytes)
-    data[offset..offset + 8].copy_from_slice(&max_total_outstanding.to_le_bytes());
-    offset += 8;
+    data[1..33].copy_from_slice(admin);
     
     // emergency_mode (1 byte)
-    data[offset] = emergency_mode as u8;
-    offset += 1;
+    data[33] = emergency_mode as u8;
     
-    // last_updated (8 bytes)
-    data[offset..offset + 8].copy_from_slice(&last_updated.to_le_bytes());
-    offset += 8;
+    // last_updated (8 bytes, aligned)
+    data[40..48].copy_from_slice(&last_updated.to_le_bytes());
     
     // has_pending_admin (1 byte)
-    data[offset] = if has_pending_admin { 1 } else { 0 };
-    offset += 1;
+    data[48] = if has_pending_admin { 1 } else { 0 };
     
     // pending_admin (32 bytes)
-    data[offset..offset + 32].copy_from_slice(pending_admin);
-    offset += 32;
+    data[49..81].copy_from_slice(pending_admin);
     
-    // admin_transfer_timestamp (8 bytes)
-    data[offset..offset + 8].copy_from_slice(&admin_transfer_timestamp.to_le_bytes());
+    // admin_transfer_timestamp (8 bytes, aligned)
+    data[88..96].copy_from_slice(&admin_transfer_timestamp.to_le_bytes());
+    
+    // mode_limit_bps (4 x 2 bytes), defaults; mode_fee_surcharge_bps, pause bitmap and guardians left at zero
+    for (i, bps) in EmergencyState::DEFAULT_MODE_LIMIT_BPS.iter().enumerate() {
+        data[96 + i * 2..98 + i * 2].copy_from_slice(&bps.to_le_bytes());
+    }
     
     data
 }
@@ -76,25 +84,25 @@ fn create_emergency_state_data(
 fn test_initialize_emergency_instruction() {
     let mollusk = mollusk();
     
-    let emergency_account = SolanaPubkey::new_unique();
+    let emergency_account = pool();
+    let (audit_log, _) = SolanaPubkey::find_program_address(&[b"audit", pool().as_ref()], &PROGRAM);
+    let (keepers, _) = SolanaPubkey::find_program_address(&[b"keepers", pool().as_ref()], &PROGRAM);
     let admin = ADMIN;
     let payer = PAYER;
     let system_program = solana_sdk::system_program::id();
     
-    let max_loan_amount: u64 = 10_000_000_000; // 10 SOL
-    let max_total_outstanding: u64 = 100_000_000_000; // 100 SOL
-    
     // initialize emergency instruction accounts
     let accounts = vec![
         AccountMeta::new(emergency_account, false),     // emergency account
+        AccountMeta::new(audit_log, false),             // audit log PDA
+        AccountMeta::new(keepers, false),               // keeper whitelist PDA
         AccountMeta::new_readonly(admin, true),         // admin (signer)
         AccountMeta::new(payer, true),                  // payer (signer)
+        AccountMeta::new_readonly(system_program, false), // system program
     ];
     
-    // instruction data: discriminator(10) + max_loan_amount + max_total_outstanding
-    let mut instruction_data = vec![10]; // Initialize discriminator
-    instruction_data.extend_from_slice(&max_loan_amount.to_le_bytes());
-    instruction_data.extend_from_slice(&max_total_outstanding.to_le_bytes());
+    // instruction data: discriminator(10), the pool id defaulting to 0
+    let instruction_data = vec![10]; // Initialize discriminator
     
     let instruction = Instruction::new_with_bytes(
         PROGRAM,
@@ -107,6 +115,8 @@ fn test_initialize_emergency_instruction() {
     
     let tx_accounts = vec![
         (emergency_account, Account::new(0, 0, &system_program)),
+        (audit_log, Account::new(0, 0, &system_program)),
+        (keepers, Account::new(0, 0, &system_program)),
         (admin, Account::new(
             10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
             0,
@@ -129,15 +139,12 @@ fn test_initialize_emergency_instruction() {
     match result.program_result {
         ProgramResult::Success => {
             println!("✅ Initialize emergency instruction executed successfully!");
-            assert!(true);
         }
         ProgramResult::Failure(err) => {
             println!("⚠️  Initialize emergency instruction failed with: {:?}", err);
-            assert!(true);
         }
         ProgramResult::UnknownError(err) => {
             println!("⚠️  Initialize emergency instruction failed with unknown error: {:?}", err);
-            assert!(true);
         }
     }
 }
@@ -145,6 +152,7 @@ fn test_initialize_emergency_instruction() {
 #[test]
 fn test_pause_instruction() {
     let mollusk = mollusk();
+    let (audit_log, audit_log_account) = create_audit_log_account();
     
     let emergency_account = SolanaPubkey::new_unique();
     let admin = ADMIN;
@@ -153,8 +161,6 @@ fn test_pause_instruction() {
     let existing_state_data = create_emergency_state_data(
         false,                          // not paused
         &admin_pubkey,
-        10_000_000_000,
-        100_000_000_000,
         EmergencyMode::Normal,
         1234567890,
         false,
@@ -165,6 +171,7 @@ fn test_pause_instruction() {
     // pause instruction accounts
     let accounts = vec![
         AccountMeta::new(emergency_account, false),     // emergency account
+        AccountMeta::new(audit_log, false),             // audit log PDA
         AccountMeta::new_readonly(admin, true),         // admin (signer)
     ];
     
@@ -182,12 +189,13 @@ fn test_pause_instruction() {
     
     let tx_accounts = vec![
         (emergency_account, Account {
-            lamports: rent.minimum_balance(113),
+            lamports: rent.minimum_balance(640),
             data: existing_state_data,
             owner: PROGRAM,
             executable: false,
             rent_epoch: 0,
         }),
+        (audit_log, audit_log_account),
         (admin, Account::new(
             10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
             0,
@@ -204,15 +212,12 @@ fn test_pause_instruction() {
     match result.program_result {
         ProgramResult::Success => {
             println!("✅ Pause instruction executed successfully!");
-            assert!(true);
         }
         ProgramResult::Failure(err) => {
             println!("⚠️  Pause instruction failed with: {:?}", err);
-            assert!(true);
         }
         ProgramResult::UnknownError(err) => {
             println!("⚠️  Pause instruction failed with unknown error: {:?}", err);
-            assert!(true);
         }
     }
 }
@@ -220,6 +225,7 @@ fn test_pause_instruction() {
 #[test]
 fn test_unpause_instruction() {
     let mollusk = mollusk();
+    let (audit_log, audit_log_account) = create_audit_log_account();
     
     let emergency_account = SolanaPubkey::new_unique();
     let admin = ADMIN;
@@ -229,8 +235,6 @@ fn test_unpause_instruction() {
     let existing_state_data = create_emergency_state_data(
         true,                           // paused
         &admin_pubkey,
-        10_000_000_000,
-        100_000_000_000,
         EmergencyMode::Normal,
         1234567890,
         false,
@@ -241,6 +245,7 @@ fn test_unpause_instruction() {
     // unpause instruction accounts
     let accounts = vec![
         AccountMeta::new(emergency_account, false),     // emergency account
+        AccountMeta::new(audit_log, false),             // audit log PDA
         AccountMeta::new_readonly(admin, true),         // admin (signer)
     ];
 
@@ -258,12 +263,13 @@ fn test_unpause_instruction() {
     
     let tx_accounts = vec![
         (emergency_account, Account {
-            lamports: rent.minimum_balance(113),
+            lamports: rent.minimum_balance(640),
             data: existing_state_data,
             owner: PROGRAM,
             executable: false,
             rent_epoch: 0,
         }),
+        (audit_log, audit_log_account),
         (admin, Account::new(
             10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
             0,
@@ -280,15 +286,12 @@ fn test_unpause_instruction() {
     match result.program_result {
         ProgramResult::Success => {
             println!("✅ Unpause instruction executed successfully!");
-            assert!(true);
         }
         ProgramResult::Failure(err) => {
             println!("⚠️  Unpause instruction failed with: {:?}", err);
-            assert!(true);
         }
         ProgramResult::UnknownError(err) => {
             println!("⚠️  Unpause instruction failed with unknown error: {:?}", err);
-            assert!(true);
         }
     }
 }
@@ -296,6 +299,7 @@ fn test_unpause_instruction() {
 #[test]
 fn test_set_emergency_mode_instruction() {
     let mollusk = mollusk();
+    let (audit_log, audit_log_account) = create_audit_log_account();
     
     let emergency_account = SolanaPubkey::new_unique();
     let admin = ADMIN;
@@ -306,8 +310,6 @@ fn test_set_emergency_mode_instruction() {
     let existing_state_data = create_emergency_state_data(
         false,
         &admin_pubkey,
-        10_000_000_000,
-        100_000_000_000,
         EmergencyMode::Normal,
         1234567890,
         false,
@@ -318,6 +320,7 @@ fn test_set_emergency_mode_instruction() {
     // set emergency mode instruction accounts
     let accounts = vec![
         AccountMeta::new(emergency_account, false),     // emergency account
+        AccountMeta::new(audit_log, false),             // audit log PDA
         AccountMeta::new_readonly(admin, true),         // admin (signer)
     ];
 
@@ -336,12 +339,13 @@ fn test_set_emergency_mode_instruction() {
     
     let tx_accounts = vec![
         (emergency_account, Account {
-            lamports: rent.minimum_balance(113),
+            lamports: rent.minimum_balance(640),
             data: existing_state_data,
             owner: PROGRAM,
             executable: false,
             rent_epoch: 0,
         }),
+        (audit_log, audit_log_account),
         (admin, Account::new(
             10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
             0,
@@ -358,15 +362,1005 @@ fn test_set_emergency_mode_instruction() {
     match result.program_result {
         ProgramResult::Success => {
             println!("✅ Set emergency mode instruction executed successfully!");
-            assert!(true);
         }
         ProgramResult::Failure(err) => {
             println!("⚠️  Set emergency mode instruction failed with: {:?}", err);
-            assert!(true);
         }
         ProgramResult::UnknownError(err) => {
             println!("⚠️  Set emergency mode instruction failed with unknown error: {:?}", err);
-            assert!(true);
         }
     }
 }
+
+#[test]
+fn test_update_limits_instruction() {
+    let mollusk = mollusk();
+    let (audit_log, audit_log_account) = create_audit_log_account();
+    
+    let emergency_account = SolanaPubkey::new_unique();
+    let admin = ADMIN;
+    let admin_pubkey: Pubkey = admin.to_bytes();
+    
+    // create existing emergency state
+    let existing_state_data = create_emergency_state_data(
+        false,
+        &admin_pubkey,
+        EmergencyMode::Normal,
+        1234567890,
+        false,
+        &[0u8; 32],
+        0,
+    );
+
+    // update limits instruction accounts
+    let accounts = vec![
+        AccountMeta::new(emergency_account, false),     // emergency account
+        AccountMeta::new(audit_log, false),             // audit log PDA
+        AccountMeta::new_readonly(admin, true),         // admin (signer)
+    ];
+
+    // instruction data: discriminator(14) + mode_limit_bps + mode_fee_surcharge_bps,
+    // tightening the defaults so it applies without going through the timelock
+    let mut instruction_data = vec![14];
+    for bps in [10_000u16, 2_500, 1_000, 0] {
+        instruction_data.extend_from_slice(&bps.to_le_bytes());
+    }
+    for bps in [0u16, 5, 25, 0] {
+        instruction_data.extend_from_slice(&bps.to_le_bytes());
+    }
+    
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &instruction_data,
+        accounts,
+    );
+    
+    let rent = Rent::default();
+    let system_program = solana_sdk::system_program::id();
+    
+    let tx_accounts = vec![
+        (emergency_account, Account {
+            lamports: rent.minimum_balance(640),
+            data: existing_state_data,
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (audit_log, audit_log_account),
+        (admin, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+    ];
+    
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &tx_accounts,
+        &[],
+    );
+    
+    match result.program_result {
+        ProgramResult::Success => {
+            println!("✅ Update limits instruction executed successfully!");
+        }
+        ProgramResult::Failure(err) => {
+            println!("⚠️  Update limits instruction failed with: {:?}", err);
+        }
+        ProgramResult::UnknownError(err) => {
+            println!("⚠️  Update limits instruction failed with unknown error: {:?}", err);
+        }
+    }
+}
+
+#[test]
+fn test_mode_limit_multipliers() {
+    let mut emergency_state = EmergencyState {
+        is_paused: false,
+        admin: ADMIN.to_bytes(),
+        emergency_mode: EmergencyMode::Normal,
+        last_updated: 0,
+        has_pending_admin: false,
+        pending_admin: [0u8; 32],
+        admin_transfer_timestamp: 0,
+        mode_limit_bps: [10_000, 7_500, 1_000, 0],
+        mode_fee_surcharge_bps: [0, 5, 25, 0],
+        paused_instructions: 0,
+        guardians: [[0u8; 32]; 5],
+        guardian_count: 0,
+        admin_signers: [[0u8; 32]; 5],
+        admin_signer_count: 0,
+        admin_threshold: 0,
+        pause_nonces: [0; 5],
+        pause_expiry: 0,
+        last_heartbeat: 0,
+        heartbeat_interval: 0,
+        dead_man_mode: EmergencyMode::Limited,
+        is_permissioned: false,
+        has_gate_program: false,
+        gate_program: [0u8; 32],
+        referral_share_bps: 0,
+        has_treasury: false,
+        treasury: [0u8; 32],
+        sweep_when_frozen: false,
+        pool_id: 0,
+        has_recovery: false,
+        recovery: [0u8; 32],
+        mode_changed_at: 0,
+        pause_nonce_floor: 0,
+    };
+    
+    assert_eq!(emergency_state.mode_loan_limit(1_000_000), 1_000_000);
+    assert_eq!(emergency_state.mode_fee_surcharge(), 0);
+    
+    emergency_state.emergency_mode = EmergencyMode::Limited;
+    assert_eq!(emergency_state.mode_loan_limit(1_000_000), 750_000);
+    assert_eq!(emergency_state.mode_fee_surcharge(), 5);
+    
+    emergency_state.emergency_mode = EmergencyMode::Emergency;
+    assert_eq!(emergency_state.mode_loan_limit(1_000_000), 100_000);
+    assert_eq!(emergency_state.mode_fee_surcharge(), 25);
+    
+    // no overflow on large limits
+    assert_eq!(emergency_state.mode_loan_limit(u64::MAX), u64::MAX / 10);
+}
+
+#[test]
+fn test_pause_mint_instruction() {
+    let mollusk = mollusk();
+    let (audit_log, audit_log_account) = create_audit_log_account();
+    
+    let emergency_account = SolanaPubkey::new_unique();
+    let admin = ADMIN;
+    let admin_pubkey: Pubkey = admin.to_bytes();
+    let mint = SolanaPubkey::new_unique();
+    let (reserve, reserve_bump) = SolanaPubkey::find_program_address(&[b"reserve", pool().as_ref(), mint.as_ref()], &PROGRAM);
+    
+    let existing_state_data = create_emergency_state_data(
+        false,
+        &admin_pubkey,
+        EmergencyMode::Normal,
+        1234567890,
+        false,
+        &[0u8; 32],
+        0,
+    );
+    
+    let mut reserve_data = vec![0u8; 200]; // Reserve size
+    reserve_data[0..32].copy_from_slice(&mint.to_bytes());
+    reserve_data[56] = 1; // is_enabled
+    reserve_data[58] = reserve_bump;
+    reserve_data[120..122].copy_from_slice(&9u16.to_le_bytes()); // fee_bps
+    
+    // pause instruction accounts, scoped to a single mint
+    let accounts = vec![
+        AccountMeta::new(emergency_account, false),     // emergency account
+        AccountMeta::new(audit_log, false),             // audit log PDA
+        AccountMeta::new_readonly(admin, true),         // admin (signer)
+        AccountMeta::new(reserve, false),               // reserve of the mint to pause
+        AccountMeta::new_readonly(mint, false),         // mint
+    ];
+    
+    // instruction data: discriminator(11) + per-mint flag + paused instruction flags
+    let mut instruction_data = vec![11, 1];
+    instruction_data.extend_from_slice(&PAUSE_LOAN.to_le_bytes());
+    
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &instruction_data,
+        accounts,
+    );
+    
+    let rent = Rent::default();
+    let system_program = solana_sdk::system_program::id();
+    
+    let tx_accounts = vec![
+        (emergency_account, Account {
+            lamports: rent.minimum_balance(640),
+            data: existing_state_data,
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (audit_log, audit_log_account),
+        (admin, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+        (reserve, Account {
+            lamports: rent.minimum_balance(200),
+            data: reserve_data,
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (mint, Account::new(0, 0, &system_program)),
+    ];
+    
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &tx_accounts,
+        &[],
+    );
+    
+    match result.program_result {
+        ProgramResult::Success => {
+            println!("✅ Mint pause instruction executed successfully!");
+        }
+        ProgramResult::Failure(err) => {
+            println!("⚠️  Mint pause instruction failed with: {:?}", err);
+        }
+        ProgramResult::UnknownError(err) => {
+            println!("⚠️  Mint pause instruction failed with unknown error: {:?}", err);
+        }
+    }
+}
+
+#[test]
+fn test_pause_scopes() {
+    let mut emergency_state = EmergencyState {
+        is_paused: false,
+        admin: ADMIN.to_bytes(),
+        emergency_mode: EmergencyMode::Normal,
+        last_updated: 0,
+        has_pending_admin: false,
+        pending_admin: [0u8; 32],
+        admin_transfer_timestamp: 0,
+        mode_limit_bps: EmergencyState::DEFAULT_MODE_LIMIT_BPS,
+        mode_fee_surcharge_bps: [0; 4],
+        paused_instructions: PAUSE_LOAN,
+        guardians: [[0u8; 32]; 5],
+        guardian_count: 0,
+        admin_signers: [[0u8; 32]; 5],
+        admin_signer_count: 0,
+        admin_threshold: 0,
+        pause_nonces: [0; 5],
+        pause_expiry: 0,
+        last_heartbeat: 0,
+        heartbeat_interval: 0,
+        dead_man_mode: EmergencyMode::Limited,
+        is_permissioned: false,
+        has_gate_program: false,
+        gate_program: [0u8; 32],
+        referral_share_bps: 0,
+        has_treasury: false,
+        treasury: [0u8; 32],
+        sweep_when_frozen: false,
+        pool_id: 0,
+        has_recovery: false,
+        recovery: [0u8; 32],
+        mode_changed_at: 0,
+        pause_nonce_floor: 0,
+    };
+    
+    // loans blocked, withdrawals and repayments still open
+    assert!(emergency_state.is_instruction_paused(PAUSE_LOAN));
+    assert!(!emergency_state.is_instruction_paused(PAUSE_WITHDRAW));
+    assert!(!emergency_state.is_instruction_paused(PAUSE_REPAY));
+    
+    // the global pause covers every instruction
+    emergency_state.is_paused = true;
+    assert!(emergency_state.is_instruction_paused(PAUSE_WITHDRAW));
+    
+    let reserve = Reserve {
+        mint: SolanaPubkey::new_unique().to_bytes(),
+        max_loan_amount: 1_000_000,
+        max_total_outstanding: 1_500_000,
+        total_outstanding: 0,
+        is_enabled: true,
+        decimals: 6,
+        bump: 255,
+        paused_instructions: PAUSE_LOAN | PAUSE_REPAY,
+        volume_threshold: 0,
+        volume_window_start: 0,
+        volume_current: 0,
+        volume_previous: 0,
+        fee_tier_volumes: [0; 3],
+        fee_bps: 9,
+        fee_tier_bps: [0; 3],
+        fee_curve_utilization_bps: [0; 4],
+        fee_curve_bps: [0; 4],
+        min_fee: 0,
+        accrued_fees: 0,
+        pause_expiry: 0,
+        vault: [0; 32],
+    };
+    assert!(reserve.is_instruction_paused(PAUSE_LOAN));
+    assert!(!reserve.is_instruction_paused(PAUSE_WITHDRAW));
+    
+    // a guardian's mint pause lapses like the protocol-wide one unless the admin ratifies it
+    let mut reserve = reserve;
+    reserve.paused_instructions = 0;
+    reserve.pause(PAUSE_LOAN, 1_000, 0);
+    reserve.expire_pause(1_000);
+    assert!(!reserve.is_instruction_paused(PAUSE_LOAN));
+    reserve.pause(PAUSE_LOAN, 3_000, 2_000);
+    reserve.pause(PAUSE_LOAN, 0, 2_500);
+    reserve.expire_pause(10_000);
+    assert!(reserve.is_instruction_paused(PAUSE_LOAN));
+}
+
+#[test]
+fn test_pause_instruction_data() {
+    // empty data pauses and unpauses everything protocol-wide
+    let pause = PauseInstructionData::try_from([].as_slice()).unwrap();
+    assert!(!pause.per_mint && pause.instructions == 0 && pause.duration == 0);
+    let unpause = UnpauseInstructionData::try_from([].as_slice()).unwrap();
+    assert!(!unpause.per_mint && unpause.instructions == 0);
+
+    // the same leading flag scopes both to the mint of the reserve passed after the authority
+    let mut data = vec![1];
+    data.extend_from_slice(&PAUSE_LOAN.to_le_bytes());
+    data.extend_from_slice(&3_600i64.to_le_bytes());
+    data.push(7);
+    let pause = PauseInstructionData::try_from(data.as_slice()).unwrap();
+    assert!(pause.per_mint);
+    assert_eq!(pause.instructions, PAUSE_LOAN);
+    assert_eq!(pause.duration, 3_600);
+    assert_eq!(pause.reason, 7);
+
+    let unpause = UnpauseInstructionData::try_from([1, PAUSE_LOAN as u8, 0, 7].as_slice()).unwrap();
+    assert!(unpause.per_mint);
+    assert_eq!(unpause.instructions, PAUSE_LOAN);
+    assert_eq!(unpause.reason, 7);
+
+    // the flag is a boolean and comes with the instruction flags
+    assert!(PauseInstructionData::try_from([2, 0, 0].as_slice()).is_err());
+    assert!(UnpauseInstructionData::try_from([1, 0].as_slice()).is_err());
+}
+
+#[test]
+fn test_set_guardians_instruction() {
+    let mollusk = mollusk();
+    let (audit_log, audit_log_account) = create_audit_log_account();
+    
+    let emergency_account = SolanaPubkey::new_unique();
+    let admin = ADMIN;
+    let admin_pubkey: Pubkey = admin.to_bytes();
+    
+    let existing_state_data = create_emergency_state_data(
+        false,
+        &admin_pubkey,
+        EmergencyMode::Normal,
+        1234567890,
+        false,
+        &[0u8; 32],
+        0,
+    );
+    
+    // set guardians instruction accounts
+    let accounts = vec![
+        AccountMeta::new(emergency_account, false),     // emergency account
+        AccountMeta::new(audit_log, false),             // audit log PDA
+        AccountMeta::new_readonly(admin, true),         // admin (signer)
+    ];
+    
+    // instruction data: discriminator(17) + guardian count + guardian keys
+    let mut instruction_data = vec![17, 1];
+    instruction_data.extend_from_slice(&GUARDIAN.to_bytes());
+    
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &instruction_data,
+        accounts,
+    );
+    
+    let rent = Rent::default();
+    let system_program = solana_sdk::system_program::id();
+    
+    let tx_accounts = vec![
+        (emergency_account, Account {
+            lamports: rent.minimum_balance(640),
+            data: existing_state_data,
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (audit_log, audit_log_account),
+        (admin, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+    ];
+    
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &tx_accounts,
+        &[],
+    );
+    
+    match result.program_result {
+        ProgramResult::Success => {
+            println!("✅ Set guardians instruction executed successfully!");
+        }
+        ProgramResult::Failure(err) => {
+            println!("⚠️  Set guardians instruction failed with: {:?}", err);
+        }
+        ProgramResult::UnknownError(err) => {
+            println!("⚠️  Set guardians instruction failed with unknown error: {:?}", err);
+        }
+    }
+}
+
+#[test]
+fn test_guardian_escalation_instruction() {
+    let mollusk = mollusk();
+    let (audit_log, audit_log_account) = create_audit_log_account();
+    
+    let emergency_account = SolanaPubkey::new_unique();
+    let admin_pubkey: Pubkey = ADMIN.to_bytes();
+    
+    let mut existing_state_data = create_emergency_state_data(
+        false,
+        &admin_pubkey,
+        EmergencyMode::Normal,
+        1234567890,
+        false,
+        &[0u8; 32],
+        0,
+    );
+    // guardians[0] (32 bytes) and guardian_count (1 byte)
+    existing_state_data[114..146].copy_from_slice(&GUARDIAN.to_bytes());
+    existing_state_data[274] = 1;
+    
+    // set emergency mode instruction accounts, signed by a guardian
+    let accounts = vec![
+        AccountMeta::new(emergency_account, false),     // emergency account
+        AccountMeta::new(audit_log, false),             // audit log PDA
+        AccountMeta::new_readonly(GUARDIAN, true),      // guardian (signer)
+    ];
+    
+    // instruction data: discriminator(13) + emergency_mode, escalating to Emergency
+    let instruction_data = vec![13, EmergencyMode::Emergency as u8];
+    
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &instruction_data,
+        accounts,
+    );
+    
+    let rent = Rent::default();
+    let system_program = solana_sdk::system_program::id();
+    
+    let tx_accounts = vec![
+        (emergency_account, Account {
+            lamports: rent.minimum_balance(640),
+            data: existing_state_data,
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (audit_log, audit_log_account),
+        (GUARDIAN, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+    ];
+    
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &tx_accounts,
+        &[],
+    );
+    
+    match result.program_result {
+        ProgramResult::Success => {
+            println!("✅ Guardian escalation executed successfully!");
+        }
+        ProgramResult::Failure(err) => {
+            println!("⚠️  Guardian escalation failed with: {:?}", err);
+        }
+        ProgramResult::UnknownError(err) => {
+            println!("⚠️  Guardian escalation failed with unknown error: {:?}", err);
+        }
+    }
+}
+
+#[test]
+fn test_guardian_membership() {
+    let mut guardians = [[0u8; 32]; 5];
+    guardians[0] = GUARDIAN.to_bytes();
+    guardians[1] = PAYER.to_bytes();
+    
+    let emergency_state = EmergencyState {
+        is_paused: false,
+        admin: ADMIN.to_bytes(),
+        emergency_mode: EmergencyMode::Normal,
+        last_updated: 0,
+        has_pending_admin: false,
+        pending_admin: [0u8; 32],
+        admin_transfer_timestamp: 0,
+        mode_limit_bps: EmergencyState::DEFAULT_MODE_LIMIT_BPS,
+        mode_fee_surcharge_bps: [0; 4],
+        paused_instructions: 0,
+        guardians,
+        guardian_count: 1,
+        admin_signers: [[0u8; 32]; 5],
+        admin_signer_count: 0,
+        admin_threshold: 0,
+        pause_nonces: [0; 5],
+        pause_expiry: 0,
+        last_heartbeat: 0,
+        heartbeat_interval: 0,
+        dead_man_mode: EmergencyMode::Limited,
+        is_permissioned: false,
+        has_gate_program: false,
+        gate_program: [0u8; 32],
+        referral_share_bps: 0,
+        has_treasury: false,
+        treasury: [0u8; 32],
+        sweep_when_frozen: false,
+        pool_id: 0,
+        has_recovery: false,
+        recovery: [0u8; 32],
+        mode_changed_at: 0,
+        pause_nonce_floor: 0,
+    };
+    
+    assert!(emergency_state.is_guardian(&GUARDIAN.to_bytes()));
+    // entries past guardian_count are ignored
+    assert!(!emergency_state.is_guardian(&PAYER.to_bytes()));
+    assert!(!emergency_state.is_guardian(&ADMIN.to_bytes()));
+
+    // each guardian's slot indexes its own signed pause nonce
+    assert_eq!(emergency_state.guardian_slot(&GUARDIAN.to_bytes()), Some(0));
+    assert_eq!(emergency_state.guardian_slot(&PAYER.to_bytes()), None);
+
+    // a removed guardian re-added later doesn't start over from nonce 0
+    let mut emergency_state = emergency_state;
+    emergency_state.consume_pause_nonce(0, 42);
+    let mut others = [[0u8; 32]; 5];
+    others[0] = PAYER.to_bytes();
+    emergency_state.replace_guardians(others, 1);
+    assert_eq!(emergency_state.pause_nonces[0], 42);
+    emergency_state.consume_pause_nonce(0, 43);
+
+    let mut both = [[0u8; 32]; 5];
+    both[0] = PAYER.to_bytes();
+    both[1] = GUARDIAN.to_bytes();
+    emergency_state.replace_guardians(both, 2);
+    assert_eq!(emergency_state.pause_nonces, [43, 43, 43, 43, 43]);
+    assert_eq!(emergency_state.pause_nonce_floor, 43);
+}
+
+#[test]
+fn test_set_admin_multisig_instruction() {
+    let mollusk = mollusk();
+    let (audit_log, audit_log_account) = create_audit_log_account();
+    
+    let emergency_account = SolanaPubkey::new_unique();
+    let admin = ADMIN;
+    let admin_pubkey: Pubkey = admin.to_bytes();
+    
+    let existing_state_data = create_emergency_state_data(
+        false,
+        &admin_pubkey,
+        EmergencyMode::Normal,
+        1234567890,
+        false,
+        &[0u8; 32],
+        0,
+    );
+    
+    // set admin multisig instruction accounts
+    let accounts = vec![
+        AccountMeta::new(emergency_account, false),     // emergency account
+        AccountMeta::new(audit_log, false),             // audit log PDA
+        AccountMeta::new_readonly(admin, true),         // admin (signer)
+    ];
+    
+    // instruction data: discriminator(18) + threshold + signer count + signer keys, 2-of-3
+    let mut instruction_data = vec![18, 2, 3];
+    instruction_data.extend_from_slice(&ADMIN.to_bytes());
+    instruction_data.extend_from_slice(&GUARDIAN.to_bytes());
+    instruction_data.extend_from_slice(&PAYER.to_bytes());
+    
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &instruction_data,
+        accounts,
+    );
+    
+    let rent = Rent::default();
+    let system_program = solana_sdk::system_program::id();
+    
+    let tx_accounts = vec![
+        (emergency_account, Account {
+            lamports: rent.minimum_balance(640),
+            data: existing_state_data,
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (audit_log, audit_log_account),
+        (admin, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+    ];
+    
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &tx_accounts,
+        &[],
+    );
+    
+    match result.program_result {
+        ProgramResult::Success => {
+            println!("✅ Set admin multisig instruction executed successfully!");
+        }
+        ProgramResult::Failure(err) => {
+            println!("⚠️  Set admin multisig instruction failed with: {:?}", err);
+        }
+        ProgramResult::UnknownError(err) => {
+            println!("⚠️  Set admin multisig instruction failed with unknown error: {:?}", err);
+        }
+    }
+}
+
+#[test]
+fn test_transfer_admin_instruction() {
+    let mollusk = mollusk();
+    let (audit_log, audit_log_account) = create_audit_log_account();
+    
+    let emergency_account = SolanaPubkey::new_unique();
+    let admin = ADMIN;
+    let admin_pubkey: Pubkey = admin.to_bytes();
+    let new_admin = SolanaPubkey::new_unique();
+    
+    let existing_state_data = create_emergency_state_data(
+        false,
+        &admin_pubkey,
+        EmergencyMode::Normal,
+        1234567890,
+        false,
+        &[0u8; 32],
+        0,
+    );
+    
+    // transfer admin instruction accounts
+    let accounts = vec![
+        AccountMeta::new(emergency_account, false),     // emergency account
+        AccountMeta::new(audit_log, false),             // audit log PDA
+        AccountMeta::new_readonly(admin, true),         // admin (signer)
+    ];
+    
+    // instruction data: discriminator(15) + new admin
+    let mut instruction_data = vec![15];
+    instruction_data.extend_from_slice(&new_admin.to_bytes());
+    
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &instruction_data,
+        accounts,
+    );
+    
+    let rent = Rent::default();
+    let system_program = solana_sdk::system_program::id();
+    
+    let tx_accounts = vec![
+        (emergency_account, Account {
+            lamports: rent.minimum_balance(640),
+            data: existing_state_data,
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (audit_log, audit_log_account),
+        (admin, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+    ];
+    
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &tx_accounts,
+        &[],
+    );
+    
+    match result.program_result {
+        ProgramResult::Success => {
+            println!("✅ Transfer admin instruction executed successfully!");
+        }
+        ProgramResult::Failure(err) => {
+            println!("⚠️  Transfer admin instruction failed with: {:?}", err);
+        }
+        ProgramResult::UnknownError(err) => {
+            println!("⚠️  Transfer admin instruction failed with unknown error: {:?}", err);
+        }
+    }
+}
+
+#[test]
+fn test_admin_signer_membership() {
+    let mut admin_signers = [[0u8; 32]; 5];
+    admin_signers[0] = ADMIN.to_bytes();
+    admin_signers[1] = GUARDIAN.to_bytes();
+    admin_signers[2] = PAYER.to_bytes();
+    
+    let emergency_state = EmergencyState {
+        is_paused: false,
+        admin: ADMIN.to_bytes(),
+        emergency_mode: EmergencyMode::Normal,
+        last_updated: 0,
+        has_pending_admin: false,
+        pending_admin: [0u8; 32],
+        admin_transfer_timestamp: 0,
+        mode_limit_bps: EmergencyState::DEFAULT_MODE_LIMIT_BPS,
+        mode_fee_surcharge_bps: [0; 4],
+        paused_instructions: 0,
+        guardians: [[0u8; 32]; 5],
+        guardian_count: 0,
+        admin_signers,
+        admin_signer_count: 2,
+        admin_threshold: 2,
+        pause_nonces: [0; 5],
+        pause_expiry: 0,
+        last_heartbeat: 0,
+        heartbeat_interval: 0,
+        dead_man_mode: EmergencyMode::Limited,
+        is_permissioned: false,
+        has_gate_program: false,
+        gate_program: [0u8; 32],
+        referral_share_bps: 0,
+        has_treasury: false,
+        treasury: [0u8; 32],
+        sweep_when_frozen: false,
+        pool_id: 0,
+        has_recovery: false,
+        recovery: [0u8; 32],
+        mode_changed_at: 0,
+        pause_nonce_floor: 0,
+    };
+    
+    assert!(emergency_state.is_admin_signer(&ADMIN.to_bytes()));
+    assert!(emergency_state.is_admin_signer(&GUARDIAN.to_bytes()));
+    // entries past admin_signer_count are ignored
+    assert!(!emergency_state.is_admin_signer(&PAYER.to_bytes()));
+}
+
+/// build a single signature Ed25519 precompile instruction data, offsets pointing into itself
+fn create_ed25519_instruction_data(public_key: &[u8; 32], message: &[u8], instruction_index: u16) -> Vec<u8> {
+    let public_key_offset: u16 = 16;
+    let signature_offset: u16 = public_key_offset + 32;
+    let message_offset: u16 = signature_offset + 64;
+    
+    let mut data = vec![1, 0];
+    for value in [
+        signature_offset,
+        instruction_index,
+        public_key_offset,
+        instruction_index,
+        message_offset,
+        message.len() as u16,
+        instruction_index,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(public_key);
+    data.extend_from_slice(&[0u8; 64]); // signature
+    data.extend_from_slice(message);
+    data
+}
+
+/// build a guardian pause message
+fn create_pause_message(pool: &[u8; 32], nonce: u64, expiry: i64, instructions: u16, mint: &[u8; 32]) -> Vec<u8> {
+    let mut message = PauseMessage::PREFIX.to_vec();
+    message.extend_from_slice(pool);
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&expiry.to_le_bytes());
+    message.extend_from_slice(&instructions.to_le_bytes());
+    message.extend_from_slice(mint);
+    message
+}
+
+#[test]
+fn test_pause_signature_message() {
+    let (pool, _) = SolanaPubkey::find_program_address(&[b"emergency", &0u64.to_le_bytes()], &PROGRAM);
+    let message = create_pause_message(&pool.to_bytes(), 7, 1_700_000_000, PAUSE_LOAN, &[0u8; 32]);
+    let data = create_ed25519_instruction_data(&GUARDIAN.to_bytes(), &message, u16::MAX);
+    
+    let (public_key, signed_message) = read_ed25519_signature(&data).unwrap();
+    assert_eq!(public_key, GUARDIAN.to_bytes());
+    
+    let pause_message = PauseMessage::try_from(signed_message).unwrap();
+    assert_eq!(pause_message.pool, pool.to_bytes());
+    assert_eq!(pause_message.nonce, 7);
+    assert_eq!(pause_message.expiry, 1_700_000_000);
+    assert_eq!(pause_message.instructions, PAUSE_LOAN);
+    assert_eq!(pause_message.mint, [0u8; 32]);
+    
+    // offsets pointing into another instruction are rejected
+    let data = create_ed25519_instruction_data(&GUARDIAN.to_bytes(), &message, 0);
+    assert!(read_ed25519_signature(&data).is_err());
+    
+    // messages without the pause prefix are rejected
+    let mut message = create_pause_message(&pool.to_bytes(), 7, 1_700_000_000, PAUSE_LOAN, &[0u8; 32]);
+    message[0] ^= 1;
+    assert!(PauseMessage::try_from(message.as_slice()).is_err());
+}
+
+#[test]
+fn test_pause_expiry() {
+    let mut emergency_state = EmergencyState {
+        is_paused: false,
+        admin: ADMIN.to_bytes(),
+        emergency_mode: EmergencyMode::Normal,
+        last_updated: 0,
+        has_pending_admin: false,
+        pending_admin: [0u8; 32],
+        admin_transfer_timestamp: 0,
+        mode_limit_bps: EmergencyState::DEFAULT_MODE_LIMIT_BPS,
+        mode_fee_surcharge_bps: [0; 4],
+        paused_instructions: 0,
+        guardians: [[0u8; 32]; 5],
+        guardian_count: 0,
+        admin_signers: [[0u8; 32]; 5],
+        admin_signer_count: 0,
+        admin_threshold: 0,
+        pause_nonces: [0; 5],
+        pause_expiry: 0,
+        last_heartbeat: 0,
+        heartbeat_interval: 0,
+        dead_man_mode: EmergencyMode::Limited,
+        is_permissioned: false,
+        has_gate_program: false,
+        gate_program: [0u8; 32],
+        referral_share_bps: 0,
+        has_treasury: false,
+        treasury: [0u8; 32],
+        sweep_when_frozen: false,
+        pool_id: 0,
+        has_recovery: false,
+        recovery: [0u8; 32],
+        mode_changed_at: 0,
+        pause_nonce_floor: 0,
+    };
+    
+    // guardian pause of loans until t=1_000
+    emergency_state.pause(PAUSE_LOAN, 1_000, 0);
+    assert!(emergency_state.is_instruction_paused(PAUSE_LOAN));
+    
+    // a shorter pause doesn't shorten it, a longer one extends it
+    emergency_state.pause(PAUSE_LOAN, 500, 100);
+    assert_eq!(emergency_state.pause_expiry, 1_000);
+    emergency_state.pause(PAUSE_LOAN, 2_000, 100);
+    assert_eq!(emergency_state.pause_expiry, 2_000);
+    
+    // lifted once the deadline passes
+    emergency_state.expire_pause(1_999);
+    assert!(emergency_state.is_instruction_paused(PAUSE_LOAN));
+    emergency_state.expire_pause(2_000);
+    assert!(!emergency_state.is_instruction_paused(PAUSE_LOAN));
+    
+    // the admin ratifying a guardian pause makes it indefinite
+    emergency_state.pause(0, 3_000, 2_500);
+    emergency_state.pause(0, 0, 2_600);
+    emergency_state.expire_pause(10_000);
+    assert!(emergency_state.is_instruction_paused(PAUSE_WITHDRAW));
+}
+
+#[test]
+fn test_mode_transitions() {
+    let emergency_state = EmergencyState {
+        is_paused: false,
+        admin: ADMIN.to_bytes(),
+        emergency_mode: EmergencyMode::Emergency,
+        last_updated: 5_000,
+        has_pending_admin: false,
+        pending_admin: [0u8; 32],
+        admin_transfer_timestamp: 0,
+        mode_limit_bps: EmergencyState::DEFAULT_MODE_LIMIT_BPS,
+        mode_fee_surcharge_bps: [0; 4],
+        paused_instructions: 0,
+        guardians: [[0u8; 32]; 5],
+        guardian_count: 0,
+        admin_signers: [[0u8; 32]; 5],
+        admin_signer_count: 0,
+        admin_threshold: 0,
+        pause_nonces: [0; 5],
+        pause_expiry: 0,
+        last_heartbeat: 0,
+        heartbeat_interval: 0,
+        dead_man_mode: EmergencyMode::Limited,
+        is_permissioned: false,
+        has_gate_program: false,
+        gate_program: [0u8; 32],
+        referral_share_bps: 0,
+        has_treasury: false,
+        treasury: [0u8; 32],
+        sweep_when_frozen: false,
+        pool_id: 0,
+        has_recovery: false,
+        recovery: [0u8; 32],
+        mode_changed_at: 1_000,
+        pause_nonce_floor: 0,
+    };
+    // the dwell time counts from the last mode change, not from unrelated updates
+    let dwelled = 1_000 + MODE_DWELL_TIME;
+    
+    // escalating is always allowed
+    assert_eq!(emergency_state.check_mode_transition(EmergencyMode::Frozen, 1_000), Ok(()));
+    
+    // de-escalating one level after the dwell time
+    assert_eq!(emergency_state.check_mode_transition(EmergencyMode::Limited, dwelled), Ok(()));
+    assert_eq!(
+        emergency_state.check_mode_transition(EmergencyMode::Limited, dwelled - 1),
+        Err(FlashLoanError::ModeCooldownActive)
+    );
+    assert_eq!(
+        emergency_state.check_mode_transition(EmergencyMode::Normal, dwelled),
+        Err(FlashLoanError::ModeSkipsLevel)
+    );
+    assert_eq!(
+        emergency_state.check_mode_transition(EmergencyMode::Emergency, dwelled),
+        Err(FlashLoanError::ModeUnchanged)
+    );
+}
+
+#[test]
+fn test_heartbeat_expiry() {
+    let mut emergency_state = EmergencyState {
+        is_paused: false,
+        admin: ADMIN.to_bytes(),
+        emergency_mode: EmergencyMode::Normal,
+        last_updated: 0,
+        has_pending_admin: false,
+        pending_admin: [0u8; 32],
+        admin_transfer_timestamp: 0,
+        mode_limit_bps: EmergencyState::DEFAULT_MODE_LIMIT_BPS,
+        mode_fee_surcharge_bps: [0; 4],
+        paused_instructions: 0,
+        guardians: [[0u8; 32]; 5],
+        guardian_count: 0,
+        admin_signers: [[0u8; 32]; 5],
+        admin_signer_count: 0,
+        admin_threshold: 0,
+        pause_nonces: [0; 5],
+        pause_expiry: 0,
+        last_heartbeat: 1_000,
+        heartbeat_interval: 0,
+        dead_man_mode: EmergencyMode::Limited,
+        is_permissioned: false,
+        has_gate_program: false,
+        gate_program: [0u8; 32],
+        referral_share_bps: 0,
+        has_treasury: false,
+        treasury: [0u8; 32],
+        sweep_when_frozen: false,
+        pool_id: 0,
+        has_recovery: false,
+        recovery: [0u8; 32],
+        mode_changed_at: 0,
+        pause_nonce_floor: 0,
+    };
+    
+    // disabled switch never expires
+    assert!(!emergency_state.is_heartbeat_expired(i64::MAX));
+    
+    emergency_state.heartbeat_interval = 7 * 24 * 60 * 60;
+    assert!(!emergency_state.is_heartbeat_expired(1_000 + 7 * 24 * 60 * 60));
+    assert!(emergency_state.is_heartbeat_expired(1_001 + 7 * 24 * 60 * 60));
+}
+
+#[test]
+fn test_audit_log_layout() {
+    assert_eq!(core::mem::size_of::<AuditEntry>(), 96);
+    assert_eq!(AuditLogHeader::ACCOUNT_LEN, 16 + AUDIT_LOG_CAPACITY * 96);
+    
+    // ring buffer wraps around, overwriting the oldest entry
+    assert_eq!(AuditLogHeader::entry_offset(0), 16);
+    assert_eq!(AuditLogHeader::entry_offset(1), 16 + 96);
+    assert_eq!(AuditLogHeader::entry_offset(AUDIT_LOG_CAPACITY as u64), 16);
+    assert_eq!(AuditLogHeader::entry_offset(AUDIT_LOG_CAPACITY as u64 - 1) + 96, AuditLogHeader::ACCOUNT_LEN);
+    
+    assert_eq!(pause_bits(true, 0), 1 << 16);
+    assert_eq!(pause_bits(false, PAUSE_LOAN | PAUSE_REPAY), (PAUSE_LOAN | PAUSE_REPAY) as u64);
+    assert_eq!(pack_bps([10_000, 5_000, 2_500, 0]), 10_000 | 5_000 << 16 | 2_500 << 32);
+}
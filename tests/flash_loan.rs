@@ -1,5 +1,5 @@
 use mollusk_svm::result::{Check, ProgramResult};
-use mollusk_svm::{program, Mollusk};
+use mollusk_svm::program;
 use solana_sdk::account::Account;
 use solana_sdk::instruction::{AccountMeta, Instruction};
 use solana_sdk::native_token::LAMPORTS_PER_SOL;
@@ -7,14 +7,12 @@ use solana_sdk::pubkey;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::rent::Rent;
 
-use pinocchio_flash_loan::ID;
+use pinocchio_flash_loan::{LoanData, LoanInstructionData};
 
-pub const PROGRAM: Pubkey = Pubkey::new_from_array(ID);
 pub const PAYER: Pubkey = pubkey!("Bv1vrbzogVpKNW2iRYJXLRUEVv6gD8xd9gid1Yh6hoiQ");
 
-pub fn mollusk() -> Mollusk {
-    Mollusk::new(&PROGRAM, "target/deploy/pinocchio_flash_loan")
-}
+mod common;
+use common::{create_emergency_state_data, mollusk, pool, PROGRAM};
 
 fn create_token_account_data(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Vec<u8> {
     let mut data = vec![0u8; 165];
@@ -25,6 +23,54 @@ fn create_token_account_data(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Vec<
     data
 }
 
+/// create reserve account data for an enabled mint lent from `vault`
+fn create_reserve_data(
+    mint: &Pubkey,
+    vault: &Pubkey,
+    max_loan_amount: u64,
+    max_total_outstanding: u64,
+    total_outstanding: u64,
+    bump: u8,
+) -> Vec<u8> {
+    let mut data = vec![0u8; 200]; // Reserve size
+    data[0..32].copy_from_slice(&mint.to_bytes());
+    data[32..40].copy_from_slice(&max_loan_amount.to_le_bytes());
+    data[40..48].copy_from_slice(&max_total_outstanding.to_le_bytes());
+    data[48..56].copy_from_slice(&total_outstanding.to_le_bytes());
+    data[56] = 1; // is_enabled
+    data[57] = 6; // decimals
+    data[58] = bump;
+    data[120..122].copy_from_slice(&9u16.to_le_bytes()); // fee_bps
+    data[168..200].copy_from_slice(&vault.to_bytes());
+    data
+}
+
+/// create empty mint statistics account data
+fn create_stats_data(mint: &Pubkey, bump: u8) -> Vec<u8> {
+    let mut data = vec![0u8; 72]; // MintStats size
+    data[0..32].copy_from_slice(&mint.to_bytes());
+    data[64] = bump;
+    data
+}
+
+/// create an empty keeper whitelist account data
+fn create_keepers_data(bump: u8) -> Vec<u8> {
+    let mut data = vec![0u8; 578]; // KeeperWhitelist size
+    data[577] = bump;
+    data
+}
+
+/// create borrower history account data after a first loan of `total_borrowed`
+fn create_history_data(borrower: &Pubkey, mint: &Pubkey, total_borrowed: u64, bump: u8) -> Vec<u8> {
+    let mut data = vec![0u8; 96]; // BorrowerHistory size
+    data[0..32].copy_from_slice(&borrower.to_bytes());
+    data[32..64].copy_from_slice(&mint.to_bytes());
+    data[64..72].copy_from_slice(&total_borrowed.to_le_bytes());
+    data[72..80].copy_from_slice(&1u64.to_le_bytes()); // loan_count
+    data[88] = bump;
+    data
+}
+
 fn create_instruction_sysvar_data(
     loan_program_id: &Pubkey,
     loan_accounts: &[AccountMeta],
@@ -111,6 +157,7 @@ fn test_loan_instruction() {
     let (protocol, _protocol_bump) = Pubkey::find_program_address(
         &[
             b"protocol",
+            pool().as_ref(),
             &fee.to_le_bytes(),
             &[bump],
         ],
@@ -123,9 +170,15 @@ fn test_loan_instruction() {
     let token_program = Pubkey::new_from_array(pinocchio_token::ID);
     let system_program = solana_sdk::system_program::id();
     
+    let emergency_account = pool();
+    let (keepers, keepers_bump) = Pubkey::find_program_address(&[b"keepers", pool().as_ref()], &PROGRAM);
+    
     let mint = Pubkey::new_unique();
     let protocol_token_account = Pubkey::new_unique();
     let borrower_token_account = Pubkey::new_unique();
+    let (reserve, reserve_bump) = Pubkey::find_program_address(&[b"reserve", pool().as_ref(), mint.as_ref()], &PROGRAM);
+    let (stats, stats_bump) = Pubkey::find_program_address(&[b"stats", pool().as_ref(), mint.as_ref()], &PROGRAM);
+    let (history, _) = Pubkey::find_program_address(&[b"borrower", pool().as_ref(), borrower.as_ref(), mint.as_ref()], &PROGRAM);
     
     // create token account data
     let protocol_balance = 10000000u64; // 10 million tokens in protocol
@@ -142,14 +195,21 @@ fn test_loan_instruction() {
         AccountMeta::new_readonly(instruction_sysvar, false), // instruction sysvar
         AccountMeta::new_readonly(token_program, false), // token program
         AccountMeta::new_readonly(system_program, false), // system program
+        AccountMeta::new(emergency_account, false),    // emergency state PDA
+        AccountMeta::new_readonly(keepers, false),     // keeper whitelist PDA
+        AccountMeta::new_readonly(PROGRAM, false),     // referrer, the program id when the loan has none
         AccountMeta::new(protocol_token_account, false), // protocol token account
         AccountMeta::new(borrower_token_account, false), // borrower token account
+        AccountMeta::new(reserve, false),              // reserve PDA
+        AccountMeta::new(stats, false),                // stats PDA
+        AccountMeta::new(history, false),              // borrower history PDA, created on the first loan
     ];
     
-    // instruction data: discriminator(0) + bump + fee + amount
+    // instruction data: discriminator(0) + bump + fee + gate account count + amount
     let mut loan_instruction_data = vec![0]; 
     loan_instruction_data.push(bump);
     loan_instruction_data.extend_from_slice(&fee.to_le_bytes());
+    loan_instruction_data.push(0);
     loan_instruction_data.extend_from_slice(&loan_amount.to_le_bytes());
     
     // create instruction sysvar data with both loan and repay instructions
@@ -192,6 +252,21 @@ fn test_loan_instruction() {
             &token_program,
         )),
         (system_program, system_program_account),
+        (emergency_account, Account {
+            lamports: rent.minimum_balance(640),
+            data: create_emergency_state_data(&PAYER),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (keepers, Account {
+            lamports: rent.minimum_balance(578),
+            data: create_keepers_data(keepers_bump),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (PROGRAM, mollusk_svm::program::create_program_account_loader_v3(&PROGRAM)),
         (protocol_token_account, Account {
             lamports: rent.minimum_balance(165),
             data: protocol_token_data,
@@ -206,6 +281,21 @@ fn test_loan_instruction() {
             executable: false,
             rent_epoch: 0,
         }),
+        (reserve, Account {
+            lamports: rent.minimum_balance(200),
+            data: create_reserve_data(&mint, &protocol_token_account, loan_amount * 10, loan_amount * 100, 0, reserve_bump),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (stats, Account {
+            lamports: rent.minimum_balance(72),
+            data: create_stats_data(&mint, stats_bump),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (history, Account::new(0, 0, &system_program)),
     ];
     
     let result = mollusk.process_and_validate_instruction(
@@ -220,18 +310,17 @@ fn test_loan_instruction() {
     match result.program_result {
         ProgramResult::Success => {
             println!("✅ Loan instruction executed successfully!");
-            assert!(true);
         }
         ProgramResult::Failure(err) => {
             println!("⚠️  Loan instruction failed with: {:?}", err);
             // is expected in test environment due to CPI limitations
             // important thing is that our instruction parsing and validation works
-            assert!(true); // test passes as long as instruction is processed
+            // test passes as long as instruction is processed
         }
         ProgramResult::UnknownError(err) => {
             println!("⚠️  Loan instruction failed with unknown error: {:?}", err);
             // this might happen due to privilege escalation in token transfers
-            assert!(true); // test passes as long as instruction is processed
+            // test passes as long as instruction is processed
         }
     }
 }
@@ -240,27 +329,31 @@ fn test_loan_instruction() {
 fn test_loan_instruction_data_parsing() {
     let fee: u16 = 1000;
     let bump: u8 = 254;
+    let gate_accounts: u8 = 2;
     let amounts = vec![500000u64, 1000000u64, 2000000u64];
     
-    let mut instruction_data = vec![0];
-    instruction_data.push(bump);
+    let mut instruction_data = vec![bump];
     instruction_data.extend_from_slice(&fee.to_le_bytes());
+    instruction_data.push(gate_accounts);
     for amount in &amounts {
         instruction_data.extend_from_slice(&amount.to_le_bytes());
     }
     
-    assert_eq!(instruction_data[0], 0); // discriminator
-    assert_eq!(instruction_data[1], bump);
-    assert_eq!(u16::from_le_bytes([instruction_data[2], instruction_data[3]]), fee);
-    
-    let mut offset = 4;
-    for (i, expected_amount) in amounts.iter().enumerate() {
-        let amount_bytes = &instruction_data[offset..offset + 8];
-        let parsed_amount = u64::from_le_bytes(amount_bytes.try_into().unwrap());
-        assert_eq!(parsed_amount, *expected_amount);
-        offset += 8;
-        println!("✅ Amount {}: {}", i, parsed_amount);
-    }
+    // Loan reads the amounts in place, so they have to be 8-byte aligned to be read on the host
+    let mut buffer = vec![0u64; instruction_data.len().div_ceil(8) + 1];
+    let bytes = unsafe { core::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, buffer.len() * 8) };
+    bytes[4..4 + instruction_data.len()].copy_from_slice(&instruction_data);
+    let instruction_data = &bytes[4..4 + instruction_data.len()];
+
+    let parsed = LoanInstructionData::try_from(instruction_data).unwrap();
+    assert_eq!(parsed.bump, [bump]);
+    assert_eq!(parsed.fee, fee);
+    assert_eq!(parsed.gate_accounts, gate_accounts);
+    assert_eq!(parsed.amounts, amounts.as_slice());
+
+    // the gate account count is required, amounts are whole u64s
+    assert!(LoanInstructionData::try_from(&instruction_data[..3]).is_err());
+    assert!(LoanInstructionData::try_from(&instruction_data[..instruction_data.len() - 1]).is_err());
 }
 
 #[test]
@@ -271,6 +364,7 @@ fn test_loan_pda_derivation() {
     let (protocol_pda, _derived_bump) = Pubkey::find_program_address(
         &[
             b"protocol",
+            pool().as_ref(),
             &fee.to_le_bytes(),
             &[bump],
         ],
@@ -279,14 +373,18 @@ fn test_loan_pda_derivation() {
     assert_ne!(protocol_pda, Pubkey::default());
 }
 
-/// create loan account data with protocol token account and required balance
-fn create_loan_account_data(protocol_token_account: &Pubkey, required_balance: u64) -> Vec<u8> {
-    let mut data = vec![0u8; 40]; // loanData size: 32 bytes (pubkey) + 8 bytes (u64)
+/// create loan account data with protocol token account, required balance and borrowed amount, without referrer
+fn create_loan_account_data(protocol_token_account: &Pubkey, required_balance: u64, amount: u64, fee: u64) -> Vec<u8> {
+    let mut data = vec![0u8; 88]; // loanData size: 32 bytes (pubkey) + 3 x 8 bytes (u64) + 32 bytes (referrer)
     
     // protocol token account pubkey
     data[0..32].copy_from_slice(&protocol_token_account.to_bytes());
     // required balance (8 bytes)
     data[32..40].copy_from_slice(&required_balance.to_le_bytes());
+    // borrowed amount (8 bytes)
+    data[40..48].copy_from_slice(&amount.to_le_bytes());
+    // fee (8 bytes)
+    data[48..56].copy_from_slice(&fee.to_le_bytes());
     
     data
 }
@@ -306,6 +404,10 @@ fn test_repay_instruction() {
     let mint = Pubkey::new_unique();
     let protocol_token_account = Pubkey::new_unique();
     let _borrower_token_account = Pubkey::new_unique();
+    let (reserve, reserve_bump) = Pubkey::find_program_address(&[b"reserve", pool().as_ref(), mint.as_ref()], &PROGRAM);
+    let (stats, stats_bump) = Pubkey::find_program_address(&[b"stats", pool().as_ref(), mint.as_ref()], &PROGRAM);
+    let (history, history_bump) = Pubkey::find_program_address(&[b"borrower", pool().as_ref(), borrower.as_ref(), mint.as_ref()], &PROGRAM);
+    let emergency_account = pool();
     
     let protocol_balance = 8000000u64; // protocol has less after lending
     let borrower_balance = required_repay_amount + 100000; // borrower has enough to repay + extra
@@ -314,13 +416,17 @@ fn test_repay_instruction() {
     let _borrower_token_data = create_token_account_data(&mint, &borrower, borrower_balance);
     
     // create loan account data - simulates active loan
-    let loan_account_data = create_loan_account_data(&protocol_token_account, required_repay_amount);
+    let loan_account_data = create_loan_account_data(&protocol_token_account, required_repay_amount, loan_amount, fee_amount);
     
     // repay instruction accounts
     let repay_accounts = vec![
         AccountMeta::new(borrower, true),               
         AccountMeta::new(loan, false),                 
+        AccountMeta::new_readonly(emergency_account, false),
         AccountMeta::new(protocol_token_account, false), 
+        AccountMeta::new(reserve, false),
+        AccountMeta::new(stats, false),
+        AccountMeta::new(history, false),
     ];
     
     let repay_instruction_data = vec![1]; 
@@ -342,12 +448,19 @@ fn test_repay_instruction() {
             &system_program,
         )),
         (loan, Account {
-            lamports: rent.minimum_balance(40),
+            lamports: rent.minimum_balance(88),
             data: loan_account_data,
             owner: PROGRAM,
             executable: false,
             rent_epoch: 0,
         }),
+        (emergency_account, Account {
+            lamports: rent.minimum_balance(640),
+            data: create_emergency_state_data(&borrower),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
         (protocol_token_account, Account {
             lamports: rent.minimum_balance(165),
             data: protocol_token_data,
@@ -355,6 +468,27 @@ fn test_repay_instruction() {
             executable: false,
             rent_epoch: 0,
         }),
+        (reserve, Account {
+            lamports: rent.minimum_balance(200),
+            data: create_reserve_data(&mint, &protocol_token_account, loan_amount * 10, loan_amount * 100, loan_amount, reserve_bump),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (stats, Account {
+            lamports: rent.minimum_balance(72),
+            data: create_stats_data(&mint, stats_bump),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (history, Account {
+            lamports: rent.minimum_balance(96),
+            data: create_history_data(&borrower, &mint, loan_amount, history_bump),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
     ];
     
     mollusk.process_and_validate_instruction(
@@ -367,7 +501,7 @@ fn test_repay_instruction() {
 #[test] 
 fn test_repay_validation() {
     let repay_discriminator = 1u8;
-    let instruction_data = vec![repay_discriminator];
+    let instruction_data = [repay_discriminator];
 
     assert_eq!(instruction_data[0], 1);
     assert_eq!(instruction_data.len(), 1);
@@ -378,13 +512,26 @@ fn test_loan_data_structure() {
     let protocol_token_account = Pubkey::new_unique();
     let required_balance = 1500000u64;
     
-    let loan_data = create_loan_account_data(&protocol_token_account, required_balance);
+    let loan_amount = 1000000u64;
+    
+    let fee_amount = 50000u64;
+    
+    let loan_data = create_loan_account_data(&protocol_token_account, required_balance, loan_amount, fee_amount);
     
-    assert_eq!(loan_data.len(), 40); // 32 bytes pubkey + 8 bytes u64
+    assert_eq!(loan_data.len(), core::mem::size_of::<LoanData>()); // 32 bytes pubkey + 3 x 8 bytes u64 + 32 bytes referrer
     
     let extracted_pubkey = Pubkey::new_from_array(loan_data[0..32].try_into().unwrap());
     assert_eq!(extracted_pubkey, protocol_token_account);
     
     let extracted_balance = u64::from_le_bytes(loan_data[32..40].try_into().unwrap());
     assert_eq!(extracted_balance, required_balance);
+    
+    let extracted_amount = u64::from_le_bytes(loan_data[40..48].try_into().unwrap());
+    assert_eq!(extracted_amount, loan_amount);
+    
+    let extracted_fee = u64::from_le_bytes(loan_data[48..56].try_into().unwrap());
+    assert_eq!(extracted_fee, fee_amount);
+
+    // no referrer
+    assert_eq!(loan_data[56..88], [0u8; 32]);
 }
@@ -0,0 +1,152 @@
+use mollusk_svm::result::ProgramResult;
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+
+use pinocchio_flash_loan::{EmergencyMode, EmergencyState, SetGateProgramInstructionData, GATE_ENTRY_LEN};
+
+mod common;
+use common::{create_audit_log_account, create_emergency_state_data, mollusk, pool, ADMIN, PROGRAM};
+
+fn emergency_state() -> EmergencyState {
+    EmergencyState {
+        is_paused: false,
+        admin: ADMIN.to_bytes(),
+        emergency_mode: EmergencyMode::Normal,
+        last_updated: 0,
+        has_pending_admin: false,
+        pending_admin: [0u8; 32],
+        admin_transfer_timestamp: 0,
+        mode_limit_bps: EmergencyState::DEFAULT_MODE_LIMIT_BPS,
+        mode_fee_surcharge_bps: [0, 5, 25, 0],
+        paused_instructions: 0,
+        guardians: [[0u8; 32]; 5],
+        guardian_count: 0,
+        admin_signers: [[0u8; 32]; 5],
+        admin_signer_count: 0,
+        admin_threshold: 0,
+        pause_nonces: [0; 5],
+        pause_expiry: 0,
+        last_heartbeat: 0,
+        heartbeat_interval: 0,
+        dead_man_mode: EmergencyMode::Limited,
+        is_permissioned: false,
+        has_gate_program: false,
+        gate_program: [0u8; 32],
+        referral_share_bps: 0,
+        has_treasury: false,
+        treasury: [0u8; 32],
+        sweep_when_frozen: false,
+        pool_id: 0,
+        has_recovery: false,
+        recovery: [0u8; 32],
+        mode_changed_at: 0,
+        pause_nonce_floor: 0,
+    }
+}
+
+#[test]
+fn test_set_gate_program_instruction() {
+    let mollusk = mollusk();
+    let (audit_log, audit_log_account) = create_audit_log_account();
+
+    let emergency_account = pool();
+    let gate_program = Pubkey::new_unique();
+    let system_program = solana_sdk::system_program::id();
+
+    // set gate program instruction accounts
+    let accounts = vec![
+        AccountMeta::new(emergency_account, false),     // emergency state PDA
+        AccountMeta::new(audit_log, false),             // audit log PDA
+        AccountMeta::new_readonly(ADMIN, true),         // admin (signer)
+        AccountMeta::new_readonly(gate_program, false), // gate program
+    ];
+
+    // instruction data: discriminator(53) + has_gate_program, gating an ungated pool applies immediately
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &[53, 1],
+        accounts,
+    );
+
+    let rent = Rent::default();
+    let tx_accounts = vec![
+        (emergency_account, Account {
+            lamports: rent.minimum_balance(640),
+            data: create_emergency_state_data(&ADMIN),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (audit_log, audit_log_account),
+        (ADMIN, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+        (gate_program, Account {
+            lamports: rent.minimum_balance(0),
+            data: vec![],
+            owner: solana_sdk::bpf_loader_upgradeable::id(),
+            executable: true,
+            rent_epoch: 0,
+        }),
+    ];
+
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &tx_accounts,
+        &[],
+    );
+
+    match result.program_result {
+        ProgramResult::Success => {
+            println!("✅ Set gate program instruction executed successfully!");
+        }
+        ProgramResult::Failure(err) => {
+            println!("⚠️  Set gate program instruction failed with: {:?}", err);
+        }
+        ProgramResult::UnknownError(err) => {
+            println!("⚠️  Set gate program instruction failed with unknown error: {:?}", err);
+        }
+    }
+}
+
+#[test]
+fn test_gate_program_layout() {
+    // the gate program follows the permissioned flag in the emergency state
+    assert_eq!(core::mem::size_of::<EmergencyState>(), 640);
+    // one mint and amount per borrowed mint in the gate's instruction data
+    assert_eq!(GATE_ENTRY_LEN, 40);
+}
+
+#[test]
+fn test_set_gate_program_requires_timelock_to_loosen() {
+    let gate = Pubkey::new_unique().to_bytes();
+    let other_gate = Pubkey::new_unique().to_bytes();
+    let set = SetGateProgramInstructionData::try_from([1u8].as_slice()).unwrap();
+    let remove = SetGateProgramInstructionData::try_from([0u8, 4].as_slice()).unwrap();
+    assert_eq!(remove.reason, 4);
+
+    // gating an ungated pool applies immediately
+    let mut state = emergency_state();
+    assert!(!set.loosens(&state, &gate));
+    assert!(!remove.loosens(&state, &gate));
+    set.apply(&mut state, &gate);
+    assert!(state.has_gate_program);
+    assert_eq!(state.gate_program, gate);
+
+    // replacing or removing the gate goes through the timelock
+    assert!(!set.loosens(&state, &gate));
+    assert!(set.loosens(&state, &other_gate));
+    assert!(remove.loosens(&state, &gate));
+
+    remove.apply(&mut state, &gate);
+    assert!(!state.has_gate_program);
+    assert_eq!(state.gate_program, [0u8; 32]);
+
+    assert!(SetGateProgramInstructionData::try_from([2u8].as_slice()).is_err());
+    assert!(SetGateProgramInstructionData::try_from([].as_slice()).is_err());
+}
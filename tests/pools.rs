@@ -0,0 +1,135 @@
+use mollusk_svm::result::ProgramResult;
+use mollusk_svm::{program, Mollusk};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+
+use pinocchio_flash_loan::{
+    EmergencyState, ALLOWLIST_SEED, AUDIT_SEED, EMERGENCY_SEED, ID, KEEPERS_SEED, PROTOCOL_SEED, RESERVE_SEED, STATS_SEED, VAULT_SEED,
+};
+
+pub const PROGRAM: Pubkey = Pubkey::new_from_array(ID);
+pub const ADMIN: Pubkey = pubkey!("Bv1vrbzogVpKNW2iRYJXLRUEVv6gD8xd9gid1Yh6hoiQ");
+pub const PAYER: Pubkey = pubkey!("HZ7znC4EWr3EQm2kNTu8XWKhgfzEhPqhXFqZNm8RhyuR");
+
+pub fn mollusk() -> Mollusk {
+    Mollusk::new(&PROGRAM, "target/deploy/pinocchio_flash_loan")
+}
+
+/// emergency state PDA of the pool `pool_id`, every other PDA of the pool being derived from it
+fn pool(pool_id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[EMERGENCY_SEED, &pool_id.to_le_bytes()], &PROGRAM).0
+}
+
+#[test]
+fn test_initialize_pool_instruction() {
+    let mollusk = mollusk();
+
+    let pool_id: u64 = 1;
+    let emergency_account = pool(pool_id);
+    let (audit_log, _) = Pubkey::find_program_address(&[AUDIT_SEED, emergency_account.as_ref()], &PROGRAM);
+    let (keepers, _) = Pubkey::find_program_address(&[KEEPERS_SEED, emergency_account.as_ref()], &PROGRAM);
+    let system_program = solana_sdk::system_program::id();
+
+    // initialize emergency instruction accounts
+    let accounts = vec![
+        AccountMeta::new(emergency_account, false),       // pool emergency state PDA
+        AccountMeta::new(audit_log, false),               // pool audit log PDA
+        AccountMeta::new(keepers, false),                 // pool keeper whitelist PDA
+        AccountMeta::new_readonly(ADMIN, true),           // pool admin (signer)
+        AccountMeta::new(PAYER, true),                    // payer (signer)
+        AccountMeta::new_readonly(system_program, false), // system program
+    ];
+
+    // instruction data: discriminator(10) + pool_id
+    let mut instruction_data = vec![10];
+    instruction_data.extend_from_slice(&pool_id.to_le_bytes());
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &instruction_data,
+        accounts,
+    );
+
+    let rent = Rent::default();
+    let (_, system_program_account) = program::keyed_account_for_system_program();
+
+    let tx_accounts = vec![
+        (emergency_account, Account::new(0, 0, &system_program)),
+        (audit_log, Account::new(0, 0, &system_program)),
+        (keepers, Account::new(0, 0, &system_program)),
+        (ADMIN, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+        (PAYER, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+        (system_program, system_program_account),
+    ];
+
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &tx_accounts,
+        &[],
+    );
+
+    match result.program_result {
+        ProgramResult::Success => {
+            println!("✅ Initialize pool instruction executed successfully!");
+        }
+        ProgramResult::Failure(err) => {
+            println!("⚠️  Initialize pool instruction failed with: {:?}", err);
+        }
+        ProgramResult::UnknownError(err) => {
+            println!("⚠️  Initialize pool instruction failed with unknown error: {:?}", err);
+        }
+    }
+}
+
+#[test]
+fn test_pool_layout() {
+    // the pool id follows the treasury settings in the emergency state
+    assert_eq!(core::mem::size_of::<EmergencyState>(), 640);
+
+    let mut data = vec![0u8; 640];
+    data[576..584].copy_from_slice(&7u64.to_le_bytes());
+    let state = unsafe { *(data.as_ptr() as *const EmergencyState) };
+    assert_eq!(state.pool_id, 7);
+}
+
+#[test]
+fn test_pool_accounts_are_isolated() {
+    let (conservative, experimental) = (pool(0), pool(1));
+    assert_ne!(conservative, experimental);
+
+    // every account of a pool is derived from its emergency state, so the same mint gets its
+    // own reserve, statistics, protocol authority and vault in each pool
+    let mint = Pubkey::new_unique();
+    let fee: u16 = 500;
+    for seeds in [
+        vec![AUDIT_SEED],
+        vec![KEEPERS_SEED],
+        vec![ALLOWLIST_SEED],
+        vec![RESERVE_SEED, mint.as_ref()],
+        vec![STATS_SEED, mint.as_ref()],
+    ] {
+        let derive = |pool: &Pubkey| {
+            let mut pool_seeds = vec![seeds[0], pool.as_ref()];
+            pool_seeds.extend_from_slice(&seeds[1..]);
+            Pubkey::find_program_address(&pool_seeds, &PROGRAM).0
+        };
+        assert_ne!(derive(&conservative), derive(&experimental));
+    }
+
+    let protocol = |pool: &Pubkey| Pubkey::find_program_address(&[PROTOCOL_SEED, pool.as_ref(), &fee.to_le_bytes()], &PROGRAM).0;
+    let vault = |pool: &Pubkey| Pubkey::find_program_address(&[VAULT_SEED, protocol(pool).as_ref(), mint.as_ref()], &PROGRAM).0;
+    assert_ne!(protocol(&conservative), protocol(&experimental));
+    assert_ne!(vault(&conservative), vault(&experimental));
+}
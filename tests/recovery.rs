@@ -0,0 +1,244 @@
+use mollusk_svm::result::ProgramResult;
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+
+use pinocchio::program_error::ProgramError;
+use pinocchio_flash_loan::{EmergencyMode, EmergencyState, FlashLoanError, Reserve, SetRecoveryInstructionData};
+
+mod common;
+use common::{create_audit_log_account, create_emergency_state_data, mollusk, pool, ADMIN, PROGRAM};
+
+fn emergency_state() -> EmergencyState {
+    EmergencyState {
+        is_paused: false,
+        admin: ADMIN.to_bytes(),
+        emergency_mode: EmergencyMode::Normal,
+        last_updated: 0,
+        has_pending_admin: false,
+        pending_admin: [0u8; 32],
+        admin_transfer_timestamp: 0,
+        mode_limit_bps: EmergencyState::DEFAULT_MODE_LIMIT_BPS,
+        mode_fee_surcharge_bps: [0, 5, 25, 0],
+        paused_instructions: 0,
+        guardians: [[0u8; 32]; 5],
+        guardian_count: 0,
+        admin_signers: [[0u8; 32]; 5],
+        admin_signer_count: 0,
+        admin_threshold: 0,
+        pause_nonces: [0; 5],
+        pause_expiry: 0,
+        last_heartbeat: 0,
+        heartbeat_interval: 0,
+        dead_man_mode: EmergencyMode::Limited,
+        is_permissioned: false,
+        has_gate_program: false,
+        gate_program: [0u8; 32],
+        referral_share_bps: 0,
+        has_treasury: false,
+        treasury: [0u8; 32],
+        sweep_when_frozen: false,
+        pool_id: 0,
+        has_recovery: false,
+        recovery: [0u8; 32],
+        mode_changed_at: 0,
+        pause_nonce_floor: 0,
+    }
+}
+
+/// create an SPL token account data with the mint, owner and balance
+fn create_token_account_data(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![0u8; 165]; // SPL token account size
+    data[0..32].copy_from_slice(&mint.to_bytes());
+    data[32..64].copy_from_slice(&owner.to_bytes());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data[108] = 1; // initialized
+    data
+}
+
+/// create emergency state account data in Frozen mode with a recovery address registered
+fn create_frozen_emergency_state_data(admin: &Pubkey, recovery: &Pubkey) -> Vec<u8> {
+    let mut data = create_emergency_state_data(admin);
+    data[33] = EmergencyMode::Frozen as u8; // emergency_mode
+    data[584] = 1; // has_recovery
+    data[585..617].copy_from_slice(&recovery.to_bytes());
+    data
+}
+
+/// create reserve PDA account data with its vault registered
+fn create_reserve_data(mint: &Pubkey, vault: &Pubkey, bump: u8) -> Vec<u8> {
+    let mut data = vec![0u8; 200]; // Reserve size
+    data[0..32].copy_from_slice(&mint.to_bytes());
+    data[56] = 1; // is_enabled
+    data[57] = 6; // decimals
+    data[58] = bump;
+    data[120..122].copy_from_slice(&9u16.to_le_bytes()); // fee_bps
+    data[168..200].copy_from_slice(&vault.to_bytes());
+    data
+}
+
+#[test]
+fn test_evacuate_vault_instruction() {
+    let mollusk = mollusk();
+    let (audit_log, audit_log_account) = create_audit_log_account();
+
+    let fee: u16 = 500;
+    let bump: u8 = 255;
+    let (protocol, _) = Pubkey::find_program_address(&[b"protocol", pool().as_ref(), &fee.to_le_bytes(), &[bump]], &PROGRAM);
+    let emergency_account = pool();
+
+    let recovery = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let (reserve, reserve_bump) = Pubkey::find_program_address(&[b"reserve", pool().as_ref(), mint.as_ref()], &PROGRAM);
+    let protocol_token_account = Pubkey::new_unique();
+    let recovery_token_account = Pubkey::new_unique();
+    let token_program = Pubkey::new_from_array(pinocchio_token::ID);
+    let system_program = solana_sdk::system_program::id();
+
+    // evacuate vault instruction accounts
+    let accounts = vec![
+        AccountMeta::new_readonly(emergency_account, false), // emergency state PDA
+        AccountMeta::new(audit_log, false),                  // audit log PDA
+        AccountMeta::new_readonly(ADMIN, true),              // admin (signer)
+        AccountMeta::new_readonly(protocol, false),          // protocol PDA
+        AccountMeta::new(reserve, false),                    // reserve PDA
+        AccountMeta::new(protocol_token_account, false),     // vault to empty
+        AccountMeta::new(recovery_token_account, false),     // recovery token account
+        AccountMeta::new_readonly(token_program, false),     // token program
+    ];
+
+    // instruction data: discriminator(81) + protocol PDA bump + fee + reason
+    let mut instruction_data = vec![81, bump];
+    instruction_data.extend_from_slice(&fee.to_le_bytes());
+    instruction_data.push(1);
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &instruction_data,
+        accounts,
+    );
+
+    let rent = Rent::default();
+    let tx_accounts = vec![
+        (emergency_account, Account {
+            lamports: rent.minimum_balance(640),
+            data: create_frozen_emergency_state_data(&ADMIN, &recovery),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (audit_log, audit_log_account),
+        (ADMIN, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+        (protocol, Account::new(0, 0, &system_program)),
+        (reserve, Account {
+            lamports: rent.minimum_balance(200),
+            data: create_reserve_data(&mint, &protocol_token_account, reserve_bump),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (protocol_token_account, Account {
+            lamports: rent.minimum_balance(165),
+            data: create_token_account_data(&mint, &protocol, 10_000_000),
+            owner: token_program,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (recovery_token_account, Account {
+            lamports: rent.minimum_balance(165),
+            data: create_token_account_data(&mint, &recovery, 0),
+            owner: token_program,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (token_program, Account {
+            lamports: 0,
+            data: vec![],
+            owner: solana_sdk::bpf_loader::id(),
+            executable: true,
+            rent_epoch: 0,
+        }),
+    ];
+
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &tx_accounts,
+        &[],
+    );
+
+    match result.program_result {
+        ProgramResult::Success => {
+            println!("✅ Evacuate vault instruction executed successfully!");
+        }
+        ProgramResult::Failure(err) => {
+            println!("⚠️  Evacuate vault instruction failed with: {:?}", err);
+        }
+        ProgramResult::UnknownError(err) => {
+            println!("⚠️  Evacuate vault instruction failed with unknown error: {:?}", err);
+        }
+    }
+}
+
+#[test]
+fn test_recovery_layout() {
+    // the recovery address follows the pool id in the emergency state
+    assert_eq!(core::mem::size_of::<EmergencyState>(), 640);
+
+    let recovery = Pubkey::new_unique();
+    let data = create_frozen_emergency_state_data(&ADMIN, &recovery);
+    let state = unsafe { *(data.as_ptr() as *const EmergencyState) };
+    assert_eq!(state.emergency_mode, EmergencyMode::Frozen);
+    assert!(state.has_recovery);
+    assert_eq!(state.recovery, recovery.to_bytes());
+
+    // evacuations outside of Frozen mode fail with their own error code
+    assert_eq!(ProgramError::from(FlashLoanError::NotFrozen), ProgramError::Custom(5));
+}
+
+#[test]
+fn test_reserve_vault_layout() {
+    // the registered vault ends the reserve, the only vault Loan lends from and EvacuateVault empties
+    assert_eq!(core::mem::size_of::<Reserve>(), 200);
+
+    let mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let data = create_reserve_data(&mint, &vault, 255);
+    let reserve = unsafe { *(data.as_ptr() as *const Reserve) };
+    assert_eq!(reserve.mint, mint.to_bytes());
+    assert_eq!(reserve.vault, vault.to_bytes());
+}
+
+#[test]
+fn test_set_recovery_requires_timelock_to_loosen() {
+    let recovery = Pubkey::new_unique().to_bytes();
+    let other_recovery = Pubkey::new_unique().to_bytes();
+    let register = SetRecoveryInstructionData::try_from([1u8].as_slice()).unwrap();
+    let remove = SetRecoveryInstructionData::try_from([0u8, 3].as_slice()).unwrap();
+    assert_eq!(remove.reason, 3);
+
+    // registering the first recovery address goes through the timelock
+    let mut state = emergency_state();
+    assert!(register.loosens(&state, &recovery));
+    register.apply(&mut state, &recovery);
+    assert!(state.has_recovery);
+    assert_eq!(state.recovery, recovery);
+
+    // re-registering the same address changes nothing, replacing it goes through the timelock
+    assert!(!register.loosens(&state, &recovery));
+    assert!(register.loosens(&state, &other_recovery));
+
+    // removing it applies immediately
+    assert!(!remove.loosens(&state, &recovery));
+    remove.apply(&mut state, &recovery);
+    assert!(!state.has_recovery);
+    assert_eq!(state.recovery, [0u8; 32]);
+
+    assert!(SetRecoveryInstructionData::try_from([2u8].as_slice()).is_err());
+    assert!(SetRecoveryInstructionData::try_from([].as_slice()).is_err());
+}
@@ -0,0 +1,319 @@
+use mollusk_svm::result::ProgramResult;
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+
+use pinocchio_flash_loan::{
+    AuditAction, EmergencyMode, EmergencyState, Referral, RegisterReferrerInstructionData, SetReferralShareInstructionData,
+};
+
+mod common;
+use common::{create_audit_log_account, create_emergency_state_data, mollusk, pool, ADMIN, PROGRAM};
+
+fn emergency_state(referral_share_bps: u16) -> EmergencyState {
+    EmergencyState {
+        is_paused: false,
+        admin: ADMIN.to_bytes(),
+        emergency_mode: EmergencyMode::Normal,
+        last_updated: 0,
+        has_pending_admin: false,
+        pending_admin: [0u8; 32],
+        admin_transfer_timestamp: 0,
+        mode_limit_bps: EmergencyState::DEFAULT_MODE_LIMIT_BPS,
+        mode_fee_surcharge_bps: [0, 5, 25, 0],
+        paused_instructions: 0,
+        guardians: [[0u8; 32]; 5],
+        guardian_count: 0,
+        admin_signers: [[0u8; 32]; 5],
+        admin_signer_count: 0,
+        admin_threshold: 0,
+        pause_nonces: [0; 5],
+        pause_expiry: 0,
+        last_heartbeat: 0,
+        heartbeat_interval: 0,
+        dead_man_mode: EmergencyMode::Limited,
+        is_permissioned: false,
+        has_gate_program: false,
+        gate_program: [0u8; 32],
+        referral_share_bps,
+        has_treasury: false,
+        treasury: [0u8; 32],
+        sweep_when_frozen: false,
+        pool_id: 0,
+        has_recovery: false,
+        recovery: [0u8; 32],
+        mode_changed_at: 0,
+        pause_nonce_floor: 0,
+    }
+}
+
+/// create an SPL token account data with the mint, owner and balance
+fn create_token_account_data(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![0u8; 165]; // SPL token account size
+    data[0..32].copy_from_slice(&mint.to_bytes());
+    data[32..64].copy_from_slice(&owner.to_bytes());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data[108] = 1; // initialized
+    data
+}
+
+/// create referral PDA account data with unclaimed fees
+fn create_referral_data(referrer: &Pubkey, mint: &Pubkey, unclaimed: u64, bump: u8) -> Vec<u8> {
+    let mut data = vec![0u8; core::mem::size_of::<Referral>()];
+    data[0..32].copy_from_slice(&referrer.to_bytes());
+    data[32..64].copy_from_slice(&mint.to_bytes());
+    data[64..72].copy_from_slice(&unclaimed.to_le_bytes());
+    data[72..80].copy_from_slice(&unclaimed.to_le_bytes()); // total_earned
+    data[80] = bump;
+    data
+}
+
+/// create reserve PDA account data with its vault registered
+fn create_reserve_data(mint: &Pubkey, vault: &Pubkey, bump: u8) -> Vec<u8> {
+    let mut data = vec![0u8; 200]; // Reserve size
+    data[0..32].copy_from_slice(&mint.to_bytes());
+    data[56] = 1; // is_enabled
+    data[57] = 6; // decimals
+    data[58] = bump;
+    data[120..122].copy_from_slice(&9u16.to_le_bytes()); // fee_bps
+    data[168..200].copy_from_slice(&vault.to_bytes());
+    data
+}
+
+#[test]
+fn test_claim_referral_instruction() {
+    let mollusk = mollusk();
+
+    let fee: u16 = 500;
+    let bump: u8 = 255;
+    let (protocol, _) = Pubkey::find_program_address(&[b"protocol", pool().as_ref(), &fee.to_le_bytes(), &[bump]], &PROGRAM);
+    let emergency_account = pool();
+
+    let referrer = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let (referral, referral_bump) = Pubkey::find_program_address(&[b"referral", pool().as_ref(), referrer.as_ref(), mint.as_ref()], &PROGRAM);
+    let (reserve, reserve_bump) = Pubkey::find_program_address(&[b"reserve", pool().as_ref(), mint.as_ref()], &PROGRAM);
+    let protocol_token_account = Pubkey::new_unique();
+    let referrer_token_account = Pubkey::new_unique();
+    let token_program = Pubkey::new_from_array(pinocchio_token::ID);
+    let system_program = solana_sdk::system_program::id();
+
+    // claim referral instruction accounts
+    let accounts = vec![
+        AccountMeta::new(referrer, true),                    // referrer (signer)
+        AccountMeta::new(referral, false),                   // referral PDA
+        AccountMeta::new_readonly(protocol, false),          // protocol PDA
+        AccountMeta::new(protocol_token_account, false),     // protocol token account
+        AccountMeta::new(referrer_token_account, false),     // referrer token account
+        AccountMeta::new_readonly(token_program, false),     // token program
+        AccountMeta::new_readonly(emergency_account, false), // emergency state PDA
+        AccountMeta::new_readonly(reserve, false),           // reserve PDA registering the vault
+    ];
+
+    // instruction data: discriminator(61) + protocol PDA bump + fee
+    let mut instruction_data = vec![61, bump];
+    instruction_data.extend_from_slice(&fee.to_le_bytes());
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &instruction_data,
+        accounts,
+    );
+
+    let rent = Rent::default();
+    let tx_accounts = vec![
+        (referrer, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+        (referral, Account {
+            lamports: rent.minimum_balance(core::mem::size_of::<Referral>()),
+            data: create_referral_data(&referrer, &mint, 2_500, referral_bump),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (protocol, Account::new(0, 0, &system_program)),
+        (protocol_token_account, Account {
+            lamports: rent.minimum_balance(165),
+            data: create_token_account_data(&mint, &protocol, 10_000_000),
+            owner: token_program,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (referrer_token_account, Account {
+            lamports: rent.minimum_balance(165),
+            data: create_token_account_data(&mint, &referrer, 0),
+            owner: token_program,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (token_program, Account {
+            lamports: 0,
+            data: vec![],
+            owner: solana_sdk::bpf_loader::id(),
+            executable: true,
+            rent_epoch: 0,
+        }),
+        (emergency_account, Account {
+            lamports: rent.minimum_balance(640),
+            data: create_emergency_state_data(&ADMIN),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (reserve, Account {
+            lamports: rent.minimum_balance(200),
+            data: create_reserve_data(&mint, &protocol_token_account, reserve_bump),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+    ];
+
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &tx_accounts,
+        &[],
+    );
+
+    match result.program_result {
+        ProgramResult::Success => {
+            println!("✅ Claim referral instruction executed successfully!");
+        }
+        ProgramResult::Failure(err) => {
+            println!("⚠️  Claim referral instruction failed with: {:?}", err);
+        }
+        ProgramResult::UnknownError(err) => {
+            println!("⚠️  Claim referral instruction failed with unknown error: {:?}", err);
+        }
+    }
+}
+
+#[test]
+fn test_register_referrer_instruction() {
+    let mollusk = mollusk();
+
+    let emergency_account = pool();
+    let (audit_log, audit_log_account) = create_audit_log_account();
+    let referrer = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let (referral, _) = Pubkey::find_program_address(&[b"referral", pool().as_ref(), referrer.as_ref(), mint.as_ref()], &PROGRAM);
+    let (system_program, system_program_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    // register referrer instruction accounts
+    let accounts = vec![
+        AccountMeta::new(emergency_account, false),         // emergency state PDA
+        AccountMeta::new(audit_log, false),                 // audit log PDA
+        AccountMeta::new(ADMIN, true),                      // admin (signer, payer)
+        AccountMeta::new(referral, false),                  // referral PDA to create
+        AccountMeta::new_readonly(referrer, false),         // referrer to register
+        AccountMeta::new_readonly(mint, false),             // mint the referrer is registered on
+        AccountMeta::new_readonly(system_program, false),   // system program
+    ];
+
+    // instruction data: discriminator(63) + reason
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &[63, 4],
+        accounts,
+    );
+
+    let rent = Rent::default();
+    let tx_accounts = vec![
+        (emergency_account, Account {
+            lamports: rent.minimum_balance(640),
+            data: create_emergency_state_data(&ADMIN),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (audit_log, audit_log_account),
+        (ADMIN, Account::new(10 * LAMPORTS_PER_SOL, 0, &system_program)),
+        (referral, Account::new(0, 0, &system_program)),
+        (referrer, Account::new(0, 0, &system_program)),
+        (mint, Account::new(0, 0, &system_program)),
+        (system_program, system_program_account),
+    ];
+
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &tx_accounts,
+        &[],
+    );
+
+    match result.program_result {
+        ProgramResult::Success => {
+            println!("✅ Register referrer instruction executed successfully!");
+        }
+        ProgramResult::Failure(err) => {
+            println!("⚠️  Register referrer instruction failed with: {:?}", err);
+        }
+        ProgramResult::UnknownError(err) => {
+            println!("⚠️  Register referrer instruction failed with unknown error: {:?}", err);
+        }
+    }
+}
+
+#[test]
+fn test_register_referrer_instruction_data() {
+    assert_eq!(RegisterReferrerInstructionData::try_from([].as_slice()).unwrap().reason, 0);
+    assert_eq!(RegisterReferrerInstructionData::try_from([4u8].as_slice()).unwrap().reason, 4);
+    assert_eq!(AuditAction::RegisterReferrer as u8, 32);
+}
+
+#[test]
+fn test_referral_credit() {
+    assert_eq!(core::mem::size_of::<Referral>(), 88);
+
+    let mut referral = Referral {
+        referrer: Pubkey::new_unique().to_bytes(),
+        mint: Pubkey::new_unique().to_bytes(),
+        unclaimed: 0,
+        total_earned: 0,
+        bump: 255,
+    };
+    referral.credit(100);
+    referral.credit(50);
+    assert_eq!(referral.unclaimed, 150);
+    assert_eq!(referral.total_earned, 150);
+
+    // claiming keeps the lifetime total
+    referral.unclaimed = 0;
+    referral.credit(u64::MAX);
+    assert_eq!(referral.unclaimed, u64::MAX);
+    assert_eq!(referral.total_earned, u64::MAX);
+}
+
+#[test]
+fn test_referral_share() {
+    // 20% of the fee, rounded down in the protocol's favour
+    let state = emergency_state(2_000);
+    assert_eq!(state.referral_share(1_000), 200);
+    assert_eq!(state.referral_share(9), 1);
+    assert_eq!(state.referral_share(4), 0);
+    assert_eq!(emergency_state(10_000).referral_share(u64::MAX), u64::MAX);
+    assert_eq!(emergency_state(0).referral_share(1_000), 0);
+}
+
+#[test]
+fn test_set_referral_share_requires_timelock_to_raise() {
+    let state = emergency_state(1_000);
+
+    let lower = SetReferralShareInstructionData::try_from(500u16.to_le_bytes().as_slice()).unwrap();
+    assert!(!lower.loosens(&state));
+
+    let raise = SetReferralShareInstructionData::try_from([&2_000u16.to_le_bytes()[..], &[7]].concat().as_slice()).unwrap();
+    assert!(raise.loosens(&state));
+    assert_eq!(raise.reason, 7);
+
+    let mut updated = state;
+    raise.apply(&mut updated);
+    assert_eq!(updated.referral_share_bps, 2_000);
+
+    assert!(SetReferralShareInstructionData::try_from(10_001u16.to_le_bytes().as_slice()).is_err());
+    assert!(SetReferralShareInstructionData::try_from([1u8].as_slice()).is_err());
+}
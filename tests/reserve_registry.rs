@@ -0,0 +1,581 @@
+use mollusk_svm::result::ProgramResult;
+use mollusk_svm::program;
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+
+use pinocchio_flash_loan::{
+    fee_amount, track_loan_volume, utilization_bps, validate_reserve, BorrowerHistory, EmergencyMode, EmergencyState,
+    InitializeVaultInstructionData, MintStats, Reserve, PROTOCOL_SEED, VAULT_SEED, VOLUME_WINDOW,
+};
+
+pub const PAYER: Pubkey = pubkey!("HZ7znC4EWr3EQm2kNTu8XWKhgfzEhPqhXFqZNm8RhyuR");
+
+mod common;
+use common::{create_audit_log_account, create_emergency_state_data, mollusk, pool, ADMIN, PROGRAM};
+
+/// create SPL mint account data
+fn create_mint_data(decimals: u8) -> Vec<u8> {
+    let mut data = vec![0u8; 82];
+    data[44] = decimals;
+    data[45] = 1; // is_initialized
+    data
+}
+
+/// create reserve account data
+fn create_reserve_data(mint: &Pubkey, max_loan_amount: u64, max_total_outstanding: u64, bump: u8) -> Vec<u8> {
+    let mut data = vec![0u8; 200]; // Reserve size
+    data[0..32].copy_from_slice(&mint.to_bytes());
+    data[32..40].copy_from_slice(&max_loan_amount.to_le_bytes());
+    data[40..48].copy_from_slice(&max_total_outstanding.to_le_bytes());
+    data[56] = 1; // is_enabled
+    data[57] = 6; // decimals
+    data[58] = bump;
+    data[120..122].copy_from_slice(&9u16.to_le_bytes()); // fee_bps
+    data
+}
+
+fn emergency_state(is_paused: bool, emergency_mode: EmergencyMode) -> EmergencyState {
+    EmergencyState {
+        is_paused,
+        admin: ADMIN.to_bytes(),
+        emergency_mode,
+        last_updated: 0,
+        has_pending_admin: false,
+        pending_admin: [0u8; 32],
+        admin_transfer_timestamp: 0,
+        mode_limit_bps: EmergencyState::DEFAULT_MODE_LIMIT_BPS,
+        mode_fee_surcharge_bps: [0; 4],
+        paused_instructions: 0,
+        guardians: [[0u8; 32]; 5],
+        guardian_count: 0,
+        admin_signers: [[0u8; 32]; 5],
+        admin_signer_count: 0,
+        admin_threshold: 0,
+        pause_nonces: [0; 5],
+        pause_expiry: 0,
+        last_heartbeat: 0,
+        heartbeat_interval: 0,
+        dead_man_mode: EmergencyMode::Limited,
+        is_permissioned: false,
+        has_gate_program: false,
+        gate_program: [0u8; 32],
+        referral_share_bps: 0,
+        has_treasury: false,
+        treasury: [0u8; 32],
+        sweep_when_frozen: false,
+        pool_id: 0,
+        has_recovery: false,
+        recovery: [0u8; 32],
+        mode_changed_at: 0,
+        pause_nonce_floor: 0,
+    }
+}
+
+fn reserve(is_enabled: bool, total_outstanding: u64) -> Reserve {
+    Reserve {
+        mint: Pubkey::new_unique().to_bytes(),
+        max_loan_amount: 1_000_000,
+        max_total_outstanding: 1_500_000,
+        total_outstanding,
+        is_enabled,
+        decimals: 6,
+        bump: 255,
+        paused_instructions: 0,
+        volume_threshold: 0,
+        volume_window_start: 0,
+        volume_current: 0,
+        volume_previous: 0,
+        fee_tier_volumes: [0; 3],
+        fee_bps: 9,
+        fee_tier_bps: [0; 3],
+        fee_curve_utilization_bps: [0; 4],
+        fee_curve_bps: [0; 4],
+        min_fee: 0,
+        accrued_fees: 0,
+        pause_expiry: 0,
+        vault: [0; 32],
+    }
+}
+
+#[test]
+fn test_register_reserve_instruction() {
+    let mollusk = mollusk();
+    let (audit_log, audit_log_account) = create_audit_log_account();
+
+    let emergency_account = pool();
+    let mint = Pubkey::new_unique();
+    let (reserve, _) = Pubkey::find_program_address(&[b"reserve", pool().as_ref(), mint.as_ref()], &PROGRAM);
+    let (stats, _) = Pubkey::find_program_address(&[b"stats", pool().as_ref(), mint.as_ref()], &PROGRAM);
+    let token_program = Pubkey::new_from_array(pinocchio_token::ID);
+    let system_program = solana_sdk::system_program::id();
+
+    let max_loan_amount: u64 = 1_000_000_000; // 1,000 tokens with 6 decimals
+    let max_total_outstanding: u64 = 5_000_000_000;
+
+    // register reserve instruction accounts
+    let accounts = vec![
+        AccountMeta::new_readonly(emergency_account, false), // emergency state PDA
+        AccountMeta::new(audit_log, false),                  // audit log PDA
+        AccountMeta::new_readonly(ADMIN, true),             // admin (signer)
+        AccountMeta::new(PAYER, true),                      // payer (signer)
+        AccountMeta::new(reserve, false),                   // reserve PDA
+        AccountMeta::new_readonly(mint, false),             // mint
+        AccountMeta::new(stats, false),                     // stats PDA
+        AccountMeta::new_readonly(system_program, false),   // system program
+    ];
+
+    // instruction data: discriminator(20) + max_loan_amount + max_total_outstanding
+    let mut instruction_data = vec![20];
+    instruction_data.extend_from_slice(&max_loan_amount.to_le_bytes());
+    instruction_data.extend_from_slice(&max_total_outstanding.to_le_bytes());
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &instruction_data,
+        accounts,
+    );
+
+    let rent = Rent::default();
+    let (_, system_program_account) = program::keyed_account_for_system_program();
+
+    let tx_accounts = vec![
+        (emergency_account, Account {
+            lamports: rent.minimum_balance(640),
+            data: create_emergency_state_data(&ADMIN),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (audit_log, audit_log_account),
+        (ADMIN, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+        (PAYER, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+        (reserve, Account::new(0, 0, &system_program)),
+        (mint, Account {
+            lamports: rent.minimum_balance(82),
+            data: create_mint_data(6),
+            owner: token_program,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (stats, Account::new(0, 0, &system_program)),
+        (system_program, system_program_account),
+    ];
+
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &tx_accounts,
+        &[],
+    );
+
+    match result.program_result {
+        ProgramResult::Success => {
+            println!("✅ Register reserve instruction executed successfully!");
+        }
+        ProgramResult::Failure(err) => {
+            println!("⚠️  Register reserve instruction failed with: {:?}", err);
+        }
+        ProgramResult::UnknownError(err) => {
+            println!("⚠️  Register reserve instruction failed with unknown error: {:?}", err);
+        }
+    }
+}
+
+#[test]
+fn test_initialize_vault_instruction() {
+    let mollusk = mollusk();
+    let (audit_log, audit_log_account) = create_audit_log_account();
+
+    let emergency_account = pool();
+    let mint = Pubkey::new_unique();
+    let (reserve, _) = Pubkey::find_program_address(&[b"reserve", pool().as_ref(), mint.as_ref()], &PROGRAM);
+    let (stats, _) = Pubkey::find_program_address(&[b"stats", pool().as_ref(), mint.as_ref()], &PROGRAM);
+    let fee: u16 = 500;
+    let (protocol, _) = Pubkey::find_program_address(&[PROTOCOL_SEED, pool().as_ref(), &fee.to_le_bytes()], &PROGRAM);
+    let (vault, _) = Pubkey::find_program_address(&[VAULT_SEED, protocol.as_ref(), mint.as_ref()], &PROGRAM);
+    let token_program = Pubkey::new_from_array(pinocchio_token::ID);
+    let system_program = solana_sdk::system_program::id();
+
+    // The vault is a program address, so Loan can find it from the mint alone
+    assert!(!vault.is_on_curve());
+    assert_ne!(vault, Pubkey::find_program_address(&[VAULT_SEED, protocol.as_ref(), Pubkey::new_unique().as_ref()], &PROGRAM).0);
+
+    let max_loan_amount: u64 = 1_000_000_000;
+    let max_total_outstanding: u64 = 5_000_000_000;
+
+    // initialize vault instruction accounts, the RegisterReserve accounts followed by the vault ones
+    let accounts = vec![
+        AccountMeta::new_readonly(emergency_account, false), // emergency state PDA
+        AccountMeta::new(audit_log, false),                  // audit log PDA
+        AccountMeta::new_readonly(ADMIN, true),             // admin (signer)
+        AccountMeta::new(PAYER, true),                      // payer (signer)
+        AccountMeta::new(reserve, false),                   // reserve PDA
+        AccountMeta::new_readonly(mint, false),             // mint
+        AccountMeta::new(stats, false),                     // stats PDA
+        AccountMeta::new_readonly(system_program, false),   // system program
+        AccountMeta::new(vault, false),                     // vault token account PDA
+        AccountMeta::new_readonly(protocol, false),         // protocol PDA
+        AccountMeta::new_readonly(token_program, false),    // token program
+    ];
+
+    // instruction data: discriminator(25) + protocol fee + max_loan_amount + max_total_outstanding
+    let mut instruction_data = vec![25];
+    instruction_data.extend_from_slice(&fee.to_le_bytes());
+    instruction_data.extend_from_slice(&max_loan_amount.to_le_bytes());
+    instruction_data.extend_from_slice(&max_total_outstanding.to_le_bytes());
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &instruction_data,
+        accounts,
+    );
+
+    let rent = Rent::default();
+    let (_, system_program_account) = program::keyed_account_for_system_program();
+
+    let tx_accounts = vec![
+        (emergency_account, Account {
+            lamports: rent.minimum_balance(640),
+            data: create_emergency_state_data(&ADMIN),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (audit_log, audit_log_account),
+        (ADMIN, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+        (PAYER, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+        (reserve, Account::new(0, 0, &system_program)),
+        (mint, Account {
+            lamports: rent.minimum_balance(82),
+            data: create_mint_data(6),
+            owner: token_program,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (stats, Account::new(0, 0, &system_program)),
+        (system_program, system_program_account),
+        (vault, Account::new(0, 0, &system_program)),
+        (protocol, Account::new(0, 0, &system_program)),
+        (token_program, program::create_program_account_loader_v3(&token_program)),
+    ];
+
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &tx_accounts,
+        &[],
+    );
+
+    match result.program_result {
+        ProgramResult::Success => {
+            println!("✅ Initialize vault instruction executed successfully!");
+        }
+        ProgramResult::Failure(err) => {
+            println!("⚠️  Initialize vault instruction failed with: {:?}", err);
+        }
+        ProgramResult::UnknownError(err) => {
+            println!("⚠️  Initialize vault instruction failed with unknown error: {:?}", err);
+        }
+    }
+}
+
+#[test]
+fn test_initialize_vault_instruction_data() {
+    let fee: u16 = 500;
+
+    // Unregistered mint, registered along with its vault
+    let mut data = fee.to_le_bytes().to_vec();
+    data.extend_from_slice(&1_000_000u64.to_le_bytes());
+    data.extend_from_slice(&5_000_000u64.to_le_bytes());
+    let instruction_data = InitializeVaultInstructionData::try_from(data.as_slice()).unwrap();
+    assert_eq!(instruction_data.fee, fee.to_le_bytes());
+    assert_eq!(instruction_data.reserve.unwrap().max_loan_amount, 1_000_000);
+
+    // Registered mint whose vault is missing
+    let instruction_data = InitializeVaultInstructionData::try_from(fee.to_le_bytes().as_slice()).unwrap();
+    assert!(instruction_data.reserve.is_none());
+
+    // Truncated fee or registration data
+    assert!(InitializeVaultInstructionData::try_from([0u8].as_slice()).is_err());
+    assert!(InitializeVaultInstructionData::try_from(&data[..10]).is_err());
+}
+
+#[test]
+fn test_update_reserve_instruction() {
+    let mollusk = mollusk();
+    let (audit_log, audit_log_account) = create_audit_log_account();
+
+    let emergency_account = pool();
+    let mint = Pubkey::new_unique();
+    let (reserve, reserve_bump) = Pubkey::find_program_address(&[b"reserve", pool().as_ref(), mint.as_ref()], &PROGRAM);
+    let system_program = solana_sdk::system_program::id();
+
+    // update reserve instruction accounts
+    let accounts = vec![
+        AccountMeta::new_readonly(emergency_account, false), // emergency state PDA
+        AccountMeta::new(audit_log, false),                  // audit log PDA
+        AccountMeta::new_readonly(ADMIN, true),             // admin (signer)
+        AccountMeta::new(reserve, false),                   // reserve PDA
+        AccountMeta::new_readonly(mint, false),             // mint
+    ];
+
+    // instruction data: discriminator(21) + is_enabled + max_loan_amount + max_total_outstanding
+    let mut instruction_data = vec![21, 0]; // disable the mint
+    instruction_data.extend_from_slice(&1_000_000_000u64.to_le_bytes());
+    instruction_data.extend_from_slice(&5_000_000_000u64.to_le_bytes());
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &instruction_data,
+        accounts,
+    );
+
+    let rent = Rent::default();
+
+    let tx_accounts = vec![
+        (emergency_account, Account {
+            lamports: rent.minimum_balance(640),
+            data: create_emergency_state_data(&ADMIN),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (audit_log, audit_log_account),
+        (ADMIN, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+        (reserve, Account {
+            lamports: rent.minimum_balance(200),
+            data: create_reserve_data(&mint, 2_000_000_000, 10_000_000_000, reserve_bump),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (mint, Account::new(0, 0, &system_program)),
+    ];
+
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &tx_accounts,
+        &[],
+    );
+
+    match result.program_result {
+        ProgramResult::Success => {
+            println!("✅ Update reserve instruction executed successfully!");
+        }
+        ProgramResult::Failure(err) => {
+            println!("⚠️  Update reserve instruction failed with: {:?}", err);
+        }
+        ProgramResult::UnknownError(err) => {
+            println!("⚠️  Update reserve instruction failed with unknown error: {:?}", err);
+        }
+    }
+}
+
+#[test]
+fn test_validate_reserve_limits() {
+    let normal = emergency_state(false, EmergencyMode::Normal);
+
+    assert!(validate_reserve(&normal, &reserve(true, 0), 1_000_000).is_ok());
+    // over the per-loan limit
+    assert!(validate_reserve(&normal, &reserve(true, 0), 1_000_001).is_err());
+    // over the outstanding limit
+    assert!(validate_reserve(&normal, &reserve(true, 600_000), 1_000_000).is_err());
+    // unregistered or disabled mint
+    assert!(validate_reserve(&normal, &reserve(false, 0), 1).is_err());
+}
+
+#[test]
+fn test_validate_reserve_emergency_modes() {
+    let reserve = reserve(true, 0);
+
+    assert!(validate_reserve(&emergency_state(false, EmergencyMode::Limited), &reserve, 500_000).is_ok());
+    assert!(validate_reserve(&emergency_state(false, EmergencyMode::Limited), &reserve, 500_001).is_err());
+    assert!(validate_reserve(&emergency_state(false, EmergencyMode::Emergency), &reserve, 250_000).is_ok());
+    assert!(validate_reserve(&emergency_state(false, EmergencyMode::Emergency), &reserve, 250_001).is_err());
+    assert!(validate_reserve(&emergency_state(false, EmergencyMode::Frozen), &reserve, 1).is_err());
+    assert!(validate_reserve(&emergency_state(true, EmergencyMode::Normal), &reserve, 1).is_err());
+}
+
+#[test]
+fn test_circuit_breaker() {
+    let mut state = emergency_state(false, EmergencyMode::Normal);
+    let mut reserve = reserve(true, 0);
+    reserve.volume_threshold = 1_000_000;
+    let now = 10 * VOLUME_WINDOW;
+
+    // under the threshold
+    assert!(!track_loan_volume(&mut state, &mut reserve, 600_000, now));
+    assert_eq!(state.emergency_mode, EmergencyMode::Normal);
+
+    // the loan crossing the threshold escalates once
+    assert!(track_loan_volume(&mut state, &mut reserve, 600_000, now + 60));
+    assert_eq!(state.emergency_mode, EmergencyMode::Limited);
+    assert_eq!(state.mode_changed_at, now + 60);
+    assert!(!track_loan_volume(&mut state, &mut reserve, 100_000, now + 120));
+    assert_eq!(state.emergency_mode, EmergencyMode::Limited);
+
+    // the previous window still weighs on the rolling volume, then ages out
+    assert_eq!(reserve.window_volume(now + VOLUME_WINDOW + VOLUME_WINDOW / 2), 650_000);
+    assert_eq!(reserve.window_volume(now + 2 * VOLUME_WINDOW), 0);
+
+    // disabled circuit breaker
+    reserve.volume_threshold = 0;
+    assert!(!track_loan_volume(&mut state, &mut reserve, u64::MAX, now + 3 * VOLUME_WINDOW));
+}
+
+#[test]
+fn test_mint_stats() {
+    assert_eq!(core::mem::size_of::<MintStats>(), 72);
+
+    let mut stats = MintStats {
+        mint: Pubkey::new_unique().to_bytes(),
+        total_borrowed: 0,
+        total_fees: 0,
+        loan_count: 0,
+        largest_loan: 0,
+        bump: 255,
+    };
+
+    stats.record_loan(1_000_000);
+    stats.record_loan(250_000);
+    stats.record_repay(5_000);
+    stats.record_repay(1_250);
+
+    assert_eq!(stats.total_borrowed, 1_250_000);
+    assert_eq!(stats.loan_count, 2);
+    assert_eq!(stats.largest_loan, 1_000_000);
+    assert_eq!(stats.total_fees, 6_250);
+
+    // counters saturate rather than failing loans
+    stats.record_loan(u64::MAX);
+    assert_eq!(stats.total_borrowed, u64::MAX);
+    assert_eq!(stats.largest_loan, u64::MAX);
+}
+
+#[test]
+fn test_borrower_fee_tiers() {
+    let mut reserve = reserve(true, 0);
+    assert_eq!(reserve.borrower_fee_bps(0), Reserve::DEFAULT_FEE_BPS);
+    assert_eq!(reserve.borrower_fee_bps(u64::MAX), Reserve::DEFAULT_FEE_BPS);
+
+    reserve.fee_tier_volumes = [1_000_000, 10_000_000, 0];
+    reserve.fee_tier_bps = [7, 5, 0];
+
+    assert_eq!(reserve.borrower_fee_bps(999_999), 9);
+    assert_eq!(reserve.borrower_fee_bps(1_000_000), 7);
+    assert_eq!(reserve.borrower_fee_bps(9_999_999), 7);
+    // the highest tier reached applies, unused tiers never do
+    assert_eq!(reserve.borrower_fee_bps(10_000_000), 5);
+    assert_eq!(reserve.borrower_fee_bps(u64::MAX), 5);
+}
+
+#[test]
+fn test_borrower_history() {
+    assert_eq!(core::mem::size_of::<BorrowerHistory>(), 96);
+
+    let mut history = BorrowerHistory {
+        borrower: Pubkey::new_unique().to_bytes(),
+        mint: Pubkey::new_unique().to_bytes(),
+        total_borrowed: 0,
+        loan_count: 0,
+        total_fees: 0,
+        bump: 255,
+    };
+
+    history.record_loan(1_000_000);
+    history.record_repay(900);
+    history.record_loan(u64::MAX);
+
+    assert_eq!(history.total_borrowed, u64::MAX);
+    assert_eq!(history.loan_count, 2);
+    assert_eq!(history.total_fees, 900);
+}
+
+#[test]
+fn test_fee_curve() {
+    assert_eq!(utilization_bps(100, 10_000), 100);
+    assert_eq!(utilization_bps(9_000, 10_000), 9_000);
+    assert_eq!(utilization_bps(u64::MAX, u64::MAX / 2), 10_000);
+    assert_eq!(utilization_bps(1, 0), 10_000);
+
+    let mut reserve = reserve(true, 0);
+    assert_eq!(reserve.curve_fee_bps(10_000), 0);
+
+    // free up to 10% of the vault, then steeper past 50%
+    reserve.fee_curve_utilization_bps = [1_000, 5_000, 9_000, 0];
+    reserve.fee_curve_bps = [0, 20, 100, 0];
+
+    assert_eq!(reserve.curve_fee_bps(100), 0);
+    assert_eq!(reserve.curve_fee_bps(1_000), 0);
+    assert_eq!(reserve.curve_fee_bps(3_000), 10);
+    assert_eq!(reserve.curve_fee_bps(5_000), 20);
+    assert_eq!(reserve.curve_fee_bps(7_000), 60);
+    assert_eq!(reserve.curve_fee_bps(9_000), 100);
+    // flat past the last point
+    assert_eq!(reserve.curve_fee_bps(10_000), 100);
+
+    // interpolated from no fee at zero utilization
+    reserve.fee_curve_utilization_bps = [5_000, 0, 0, 0];
+    reserve.fee_curve_bps = [50, 0, 0, 0];
+    assert_eq!(reserve.curve_fee_bps(2_500), 25);
+}
+
+#[test]
+fn test_fee_rounding_and_minimum() {
+    // rounded up, so small loans never borrow for free
+    assert_eq!(fee_amount(1, 9, 0), Ok(1));
+    assert_eq!(fee_amount(10_000, 9, 0), Ok(9));
+    assert_eq!(fee_amount(10_001, 9, 0), Ok(10));
+    assert_eq!(fee_amount(0, 9, 0), Ok(0));
+
+    // the minimum fee applies to loans whose fee is below it
+    assert_eq!(fee_amount(10_000, 9, 100), Ok(100));
+    assert_eq!(fee_amount(1_000_000, 9, 100), Ok(900));
+
+    // no intermediate overflow on large amounts
+    assert_eq!(fee_amount(u64::MAX, 10_000, 0), Ok(u64::MAX));
+    assert_eq!(fee_amount(u64::MAX, 9, 0), Ok((u64::MAX as u128 * 9).div_ceil(10_000) as u64));
+    assert!(fee_amount(u64::MAX, 10_001, 0).is_err());
+}
+
+#[test]
+fn test_quote_fee() {
+    let mut state = emergency_state(false, EmergencyMode::Limited);
+    state.mode_fee_surcharge_bps = [0, 5, 10, 20];
+
+    let mut reserve = reserve(true, 0);
+    reserve.fee_tier_volumes = [1_000_000, 0, 0];
+    reserve.fee_tier_bps = [7, 0, 0];
+    reserve.fee_curve_utilization_bps = [5_000, 0, 0, 0];
+    reserve.fee_curve_bps = [50, 0, 0, 0];
+    reserve.min_fee = 10;
+
+    // base fee, 25 bps of curve for a quarter of the vault and the Limited surcharge
+    assert_eq!(reserve.quote_fee(&state, 0, 400_000, 100_000), Ok(100_000 * (9 + 25 + 5) / 10_000));
+    // loyalty tier reached
+    assert_eq!(reserve.quote_fee(&state, 1_000_000, 400_000, 100_000), Ok(100_000 * (7 + 25 + 5) / 10_000));
+    // minimum fee on a dust loan
+    assert_eq!(reserve.quote_fee(&state, 0, 400_000, 1), Ok(10));
+}
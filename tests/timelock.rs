@@ -0,0 +1,429 @@
+use mollusk_svm::result::ProgramResult;
+use mollusk_svm::program;
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+
+use pinocchio_flash_loan::{
+    EmergencyMode, EmergencyState, HeartbeatInstructionData, Reserve, SetEmergencyModeInstructionData, SetFeeCurveInstructionData,
+    SetFeeTiersInstructionData, SetMinFeeInstructionData, TimelockAction, UpdateLimitsInstructionData, UpdateReserveInstructionData,
+};
+
+pub const PAYER: Pubkey = pubkey!("HZ7znC4EWr3EQm2kNTu8XWKhgfzEhPqhXFqZNm8RhyuR");
+
+mod common;
+use common::{create_audit_log_account, create_emergency_state_data, mollusk, pool, ADMIN, PROGRAM};
+
+fn emergency_state(emergency_mode: EmergencyMode) -> EmergencyState {
+    EmergencyState {
+        is_paused: false,
+        admin: ADMIN.to_bytes(),
+        emergency_mode,
+        last_updated: 0,
+        has_pending_admin: false,
+        pending_admin: [0u8; 32],
+        admin_transfer_timestamp: 0,
+        mode_limit_bps: EmergencyState::DEFAULT_MODE_LIMIT_BPS,
+        mode_fee_surcharge_bps: [0, 5, 25, 0],
+        paused_instructions: 0,
+        guardians: [[0u8; 32]; 5],
+        guardian_count: 0,
+        admin_signers: [[0u8; 32]; 5],
+        admin_signer_count: 0,
+        admin_threshold: 0,
+        pause_nonces: [0; 5],
+        pause_expiry: 0,
+        last_heartbeat: 0,
+        heartbeat_interval: 0,
+        dead_man_mode: EmergencyMode::Limited,
+        is_permissioned: false,
+        has_gate_program: false,
+        gate_program: [0u8; 32],
+        referral_share_bps: 0,
+        has_treasury: false,
+        treasury: [0u8; 32],
+        sweep_when_frozen: false,
+        pool_id: 0,
+        has_recovery: false,
+        recovery: [0u8; 32],
+        mode_changed_at: 0,
+        pause_nonce_floor: 0,
+    }
+}
+
+fn update_limits_data(mode_limit_bps: [u16; 4], mode_fee_surcharge_bps: [u16; 4]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for bps in mode_limit_bps.iter().chain(mode_fee_surcharge_bps.iter()) {
+        data.extend_from_slice(&bps.to_le_bytes());
+    }
+    data
+}
+
+fn update_reserve_data(is_enabled: bool, max_loan_amount: u64, max_total_outstanding: u64) -> Vec<u8> {
+    let mut data = vec![is_enabled as u8];
+    data.extend_from_slice(&max_loan_amount.to_le_bytes());
+    data.extend_from_slice(&max_total_outstanding.to_le_bytes());
+    data
+}
+
+#[test]
+fn test_queue_operation_instruction() {
+    let mollusk = mollusk();
+    let (audit_log, audit_log_account) = create_audit_log_account();
+
+    let emergency_account = pool();
+    let id: u64 = 1;
+    let (operation, _) = Pubkey::find_program_address(&[b"timelock", pool().as_ref(), &id.to_le_bytes()], &PROGRAM);
+    let system_program = solana_sdk::system_program::id();
+
+    // queue operation instruction accounts
+    let accounts = vec![
+        AccountMeta::new_readonly(emergency_account, false), // emergency state PDA
+        AccountMeta::new(audit_log, false),                  // audit log PDA
+        AccountMeta::new_readonly(ADMIN, true),             // admin (signer)
+        AccountMeta::new(PAYER, true),                      // payer (signer)
+        AccountMeta::new(operation, false),                 // operation PDA
+        AccountMeta::new_readonly(system_program, false),   // system program
+    ];
+
+    // instruction data: discriminator(30) + id + action + target + payload, de-escalating to Normal
+    let mut instruction_data = vec![30];
+    instruction_data.extend_from_slice(&id.to_le_bytes());
+    instruction_data.push(TimelockAction::SetEmergencyMode as u8);
+    instruction_data.extend_from_slice(&[0u8; 32]);
+    instruction_data.push(EmergencyMode::Normal as u8);
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &instruction_data,
+        accounts,
+    );
+
+    let rent = Rent::default();
+    let (_, system_program_account) = program::keyed_account_for_system_program();
+
+    let tx_accounts = vec![
+        (emergency_account, Account {
+            lamports: rent.minimum_balance(640),
+            data: create_emergency_state_data(&ADMIN),
+            owner: PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }),
+        (audit_log, audit_log_account),
+        (ADMIN, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+        (PAYER, Account::new(
+            10 * LAMPORTS_PER_SOL + rent.minimum_balance(0),
+            0,
+            &system_program,
+        )),
+        (operation, Account::new(0, 0, &system_program)),
+        (system_program, system_program_account),
+    ];
+
+    let result = mollusk.process_and_validate_instruction(
+        &instruction,
+        &tx_accounts,
+        &[],
+    );
+
+    match result.program_result {
+        ProgramResult::Success => {
+            println!("✅ Queue operation instruction executed successfully!");
+        }
+        ProgramResult::Failure(err) => {
+            println!("⚠️  Queue operation instruction failed with: {:?}", err);
+        }
+        ProgramResult::UnknownError(err) => {
+            println!("⚠️  Queue operation instruction failed with unknown error: {:?}", err);
+        }
+    }
+}
+
+#[test]
+fn test_update_limits_requires_timelock_to_loosen() {
+    let state = emergency_state(EmergencyMode::Normal);
+
+    let tighten = UpdateLimitsInstructionData::try_from(
+        update_limits_data([10_000, 2_500, 1_000, 0], [0, 10, 25, 0]).as_slice()
+    ).unwrap();
+    assert!(!tighten.loosens(&state));
+
+    // raising a limit
+    let raise = UpdateLimitsInstructionData::try_from(
+        update_limits_data([10_000, 7_500, 2_500, 0], [0, 5, 25, 0]).as_slice()
+    ).unwrap();
+    assert!(raise.loosens(&state));
+
+    // lowering a fee surcharge
+    let discount = UpdateLimitsInstructionData::try_from(
+        update_limits_data([10_000, 5_000, 2_500, 0], [0, 0, 25, 0]).as_slice()
+    ).unwrap();
+    assert!(discount.loosens(&state));
+}
+
+#[test]
+fn test_update_reserve_requires_timelock_to_loosen() {
+    let reserve = Reserve {
+        mint: Pubkey::new_unique().to_bytes(),
+        max_loan_amount: 1_000_000,
+        max_total_outstanding: 1_500_000,
+        total_outstanding: 0,
+        is_enabled: false,
+        decimals: 6,
+        bump: 255,
+        paused_instructions: 0,
+        volume_threshold: 0,
+        volume_window_start: 0,
+        volume_current: 0,
+        volume_previous: 0,
+        fee_tier_volumes: [0; 3],
+        fee_bps: 9,
+        fee_tier_bps: [0; 3],
+        fee_curve_utilization_bps: [0; 4],
+        fee_curve_bps: [0; 4],
+        min_fee: 0,
+        accrued_fees: 0,
+        pause_expiry: 0,
+        vault: [0; 32],
+    };
+
+    let tighten = UpdateReserveInstructionData::try_from(update_reserve_data(false, 500_000, 1_500_000).as_slice()).unwrap();
+    assert!(!tighten.loosens(&reserve));
+
+    let enable = UpdateReserveInstructionData::try_from(update_reserve_data(true, 1_000_000, 1_500_000).as_slice()).unwrap();
+    assert!(enable.loosens(&reserve));
+
+    let raise = UpdateReserveInstructionData::try_from(update_reserve_data(false, 1_000_000, 2_000_000).as_slice()).unwrap();
+    assert!(raise.loosens(&reserve));
+}
+
+#[test]
+fn test_set_mode_requires_timelock_to_deescalate() {
+    let state = emergency_state(EmergencyMode::Emergency);
+
+    let escalate = SetEmergencyModeInstructionData::try_from([EmergencyMode::Frozen as u8].as_slice()).unwrap();
+    assert!(!escalate.loosens(&state));
+
+    let deescalate = SetEmergencyModeInstructionData::try_from([EmergencyMode::Limited as u8].as_slice()).unwrap();
+    assert!(deescalate.loosens(&state));
+}
+
+/// heartbeat instruction data reconfiguring the dead-man switch
+fn heartbeat_data(interval: i64, mode: EmergencyMode) -> Vec<u8> {
+    let mut data = interval.to_le_bytes().to_vec();
+    data.push(mode as u8);
+    data
+}
+
+#[test]
+fn test_heartbeat_requires_timelock_to_loosen_dead_man_switch() {
+    let mut state = emergency_state(EmergencyMode::Normal);
+    state.heartbeat_interval = 86_400;
+    state.dead_man_mode = EmergencyMode::Emergency;
+
+    let beat = HeartbeatInstructionData::try_from([].as_slice()).unwrap();
+    assert!(!beat.loosens(&state));
+
+    let shorter = HeartbeatInstructionData::try_from(heartbeat_data(3_600, EmergencyMode::Frozen).as_slice()).unwrap();
+    assert!(!shorter.loosens(&state));
+
+    let disable = HeartbeatInstructionData::try_from(heartbeat_data(0, EmergencyMode::Emergency).as_slice()).unwrap();
+    assert!(disable.loosens(&state));
+
+    let longer = HeartbeatInstructionData::try_from(heartbeat_data(172_800, EmergencyMode::Emergency).as_slice()).unwrap();
+    assert!(longer.loosens(&state));
+
+    let milder = HeartbeatInstructionData::try_from(heartbeat_data(86_400, EmergencyMode::Limited).as_slice()).unwrap();
+    assert!(milder.loosens(&state));
+
+    shorter.apply(&mut state);
+    assert_eq!(state.heartbeat_interval, 3_600);
+    assert_eq!(state.dead_man_mode, EmergencyMode::Frozen);
+
+    // Enabling a disabled switch tightens it whatever the interval
+    state.heartbeat_interval = 0;
+    let enable = HeartbeatInstructionData::try_from(heartbeat_data(604_800, EmergencyMode::Frozen).as_slice()).unwrap();
+    assert!(!enable.loosens(&state));
+    assert_eq!(TimelockAction::try_from(12).unwrap(), TimelockAction::SetDeadManSwitch);
+}
+
+/// set fee tiers instruction data: fee_bps, tier volumes, tier fees
+fn set_fee_tiers_data(fee_bps: u16, fee_tier_volumes: [u64; 3], fee_tier_bps: [u16; 3]) -> Vec<u8> {
+    let mut data = fee_bps.to_le_bytes().to_vec();
+    for volume in fee_tier_volumes {
+        data.extend_from_slice(&volume.to_le_bytes());
+    }
+    for bps in fee_tier_bps {
+        data.extend_from_slice(&bps.to_le_bytes());
+    }
+    data
+}
+
+#[test]
+fn test_set_fee_tiers_requires_timelock_to_lower_fees() {
+    let mut reserve = Reserve {
+        mint: Pubkey::new_unique().to_bytes(),
+        max_loan_amount: 1_000_000,
+        max_total_outstanding: 1_500_000,
+        total_outstanding: 0,
+        is_enabled: true,
+        decimals: 6,
+        bump: 255,
+        paused_instructions: 0,
+        volume_threshold: 0,
+        volume_window_start: 0,
+        volume_current: 0,
+        volume_previous: 0,
+        fee_tier_volumes: [1_000_000, 0, 0],
+        fee_bps: 9,
+        fee_tier_bps: [7, 0, 0],
+        fee_curve_utilization_bps: [0; 4],
+        fee_curve_bps: [0; 4],
+        min_fee: 0,
+        accrued_fees: 0,
+        pause_expiry: 0,
+        vault: [0; 32],
+    };
+
+    let raise = SetFeeTiersInstructionData::try_from(set_fee_tiers_data(10, [1_000_000, 0, 0], [8, 0, 0]).as_slice()).unwrap();
+    assert!(!raise.loosens(&reserve));
+
+    // dropping a tier raises the fee of the borrowers in it
+    let drop_tier = SetFeeTiersInstructionData::try_from(set_fee_tiers_data(9, [0; 3], [0; 3]).as_slice()).unwrap();
+    assert!(!drop_tier.loosens(&reserve));
+
+    let lower_base = SetFeeTiersInstructionData::try_from(set_fee_tiers_data(8, [1_000_000, 0, 0], [7, 0, 0]).as_slice()).unwrap();
+    assert!(lower_base.loosens(&reserve));
+
+    let earlier_tier = SetFeeTiersInstructionData::try_from(set_fee_tiers_data(9, [500_000, 0, 0], [7, 0, 0]).as_slice()).unwrap();
+    assert!(earlier_tier.loosens(&reserve));
+
+    let new_tier = SetFeeTiersInstructionData::try_from(set_fee_tiers_data(9, [1_000_000, 5_000_000, 0], [7, 5, 0]).as_slice()).unwrap();
+    assert!(new_tier.loosens(&reserve));
+
+    new_tier.apply(&mut reserve);
+    assert_eq!(reserve.borrower_fee_bps(5_000_000), 5);
+}
+
+#[test]
+fn test_set_fee_tiers_validation() {
+    // tier fees can't exceed the fee before them
+    assert!(SetFeeTiersInstructionData::try_from(set_fee_tiers_data(9, [1_000_000, 0, 0], [10, 0, 0]).as_slice()).is_err());
+    // thresholds strictly increase
+    assert!(SetFeeTiersInstructionData::try_from(set_fee_tiers_data(9, [1_000_000, 1_000_000, 0], [7, 5, 0]).as_slice()).is_err());
+    // unused tiers come last
+    assert!(SetFeeTiersInstructionData::try_from(set_fee_tiers_data(9, [0, 1_000_000, 0], [0, 7, 0]).as_slice()).is_err());
+    assert!(SetFeeTiersInstructionData::try_from(set_fee_tiers_data(9, [0; 3], [5, 0, 0]).as_slice()).is_err());
+    assert!(SetFeeTiersInstructionData::try_from(set_fee_tiers_data(10_001, [0; 3], [0; 3]).as_slice()).is_err());
+    assert!(SetFeeTiersInstructionData::try_from(set_fee_tiers_data(9, [0; 3], [0; 3]).as_slice()).is_ok());
+}
+
+/// set fee curve instruction data: point utilizations, point fees
+fn set_fee_curve_data(fee_curve_utilization_bps: [u16; 4], fee_curve_bps: [u16; 4]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for bps in fee_curve_utilization_bps.iter().chain(fee_curve_bps.iter()) {
+        data.extend_from_slice(&bps.to_le_bytes());
+    }
+    data
+}
+
+#[test]
+fn test_set_fee_curve_requires_timelock_to_lower_fees() {
+    let reserve = Reserve {
+        mint: Pubkey::new_unique().to_bytes(),
+        max_loan_amount: 1_000_000,
+        max_total_outstanding: 1_500_000,
+        total_outstanding: 0,
+        is_enabled: true,
+        decimals: 6,
+        bump: 255,
+        paused_instructions: 0,
+        volume_threshold: 0,
+        volume_window_start: 0,
+        volume_current: 0,
+        volume_previous: 0,
+        fee_tier_volumes: [0; 3],
+        fee_bps: 9,
+        fee_tier_bps: [0; 3],
+        fee_curve_utilization_bps: [5_000, 9_000, 0, 0],
+        fee_curve_bps: [20, 100, 0, 0],
+        min_fee: 0,
+        accrued_fees: 0,
+        pause_expiry: 0,
+        vault: [0; 32],
+    };
+
+    let raise = SetFeeCurveInstructionData::try_from(set_fee_curve_data([5_000, 9_000, 0, 0], [30, 100, 0, 0]).as_slice()).unwrap();
+    assert!(!raise.loosens(&reserve));
+
+    // reaching the top fee sooner never lowers the curve
+    let steeper = SetFeeCurveInstructionData::try_from(set_fee_curve_data([5_000, 8_000, 0, 0], [20, 100, 0, 0]).as_slice()).unwrap();
+    assert!(!steeper.loosens(&reserve));
+
+    let flatter = SetFeeCurveInstructionData::try_from(set_fee_curve_data([5_000, 9_500, 0, 0], [20, 100, 0, 0]).as_slice()).unwrap();
+    assert!(flatter.loosens(&reserve));
+
+    let lower_top = SetFeeCurveInstructionData::try_from(set_fee_curve_data([5_000, 9_000, 0, 0], [20, 90, 0, 0]).as_slice()).unwrap();
+    assert!(lower_top.loosens(&reserve));
+
+    let removed = SetFeeCurveInstructionData::try_from(set_fee_curve_data([0; 4], [0; 4]).as_slice()).unwrap();
+    assert!(removed.loosens(&reserve));
+}
+
+#[test]
+fn test_set_fee_curve_validation() {
+    // fees can't decrease with utilization
+    assert!(SetFeeCurveInstructionData::try_from(set_fee_curve_data([5_000, 9_000, 0, 0], [100, 20, 0, 0]).as_slice()).is_err());
+    // utilizations strictly increase, up to 100%
+    assert!(SetFeeCurveInstructionData::try_from(set_fee_curve_data([5_000, 5_000, 0, 0], [20, 100, 0, 0]).as_slice()).is_err());
+    assert!(SetFeeCurveInstructionData::try_from(set_fee_curve_data([10_001, 0, 0, 0], [20, 0, 0, 0]).as_slice()).is_err());
+    // unused points come last
+    assert!(SetFeeCurveInstructionData::try_from(set_fee_curve_data([0, 5_000, 0, 0], [0, 20, 0, 0]).as_slice()).is_err());
+    assert!(SetFeeCurveInstructionData::try_from(set_fee_curve_data([5_000, 0, 0, 0], [20, 5, 0, 0]).as_slice()).is_err());
+    assert!(SetFeeCurveInstructionData::try_from(set_fee_curve_data([1_000, 5_000, 9_000, 10_000], [0, 20, 100, 500]).as_slice()).is_ok());
+}
+
+#[test]
+fn test_set_min_fee_requires_timelock_to_lower() {
+    let mut reserve = Reserve {
+        mint: Pubkey::new_unique().to_bytes(),
+        max_loan_amount: 1_000_000,
+        max_total_outstanding: 1_500_000,
+        total_outstanding: 0,
+        is_enabled: true,
+        decimals: 6,
+        bump: 255,
+        paused_instructions: 0,
+        volume_threshold: 0,
+        volume_window_start: 0,
+        volume_current: 0,
+        volume_previous: 0,
+        fee_tier_volumes: [0; 3],
+        fee_bps: 9,
+        fee_tier_bps: [0; 3],
+        fee_curve_utilization_bps: [0; 4],
+        fee_curve_bps: [0; 4],
+        min_fee: 100,
+        accrued_fees: 0,
+        pause_expiry: 0,
+        vault: [0; 32],
+    };
+
+    let raise = SetMinFeeInstructionData::try_from(200u64.to_le_bytes().as_slice()).unwrap();
+    assert!(!raise.loosens(&reserve));
+
+    let lower = SetMinFeeInstructionData::try_from(50u64.to_le_bytes().as_slice()).unwrap();
+    assert!(lower.loosens(&reserve));
+
+    raise.apply(&mut reserve);
+    assert_eq!(reserve.min_fee, 200);
+    assert!(SetMinFeeInstructionData::try_from([0u8; 7].as_slice()).is_err());
+}